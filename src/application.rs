@@ -2,10 +2,9 @@ use gtk::prelude::*;
 use libadwaita as adw;
 use std::sync::Arc;
 
+use crate::backend::paths::APP_ID;
 use crate::window::MainWindow;
 
-const APP_ID: &str = "com.github.Mar0xy.ProtonUpGtk";
-
 pub struct Application {
     app: adw::Application,
     _runtime: tokio::runtime::Runtime,
@@ -34,7 +33,7 @@ impl Application {
         }
     }
 
-    pub fn run(&self) -> i32 {
-        self.app.run().into()
+    pub fn run(&self, args: &[String]) -> i32 {
+        self.app.run_with_args(args).into()
     }
 }
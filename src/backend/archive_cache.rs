@@ -0,0 +1,335 @@
+use super::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+const INDEX_FILE: &str = "index.json";
+
+/// A cached archive, stored under its SHA-256 so identical downloads share one file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub url: String,
+    pub sha256: String,
+    pub file_name: String,
+    pub size: u64,
+    /// Unix timestamp of the last install that used this entry
+    pub last_used: u64,
+}
+
+impl CacheEntry {
+    fn stored_name(&self) -> String {
+        format!("{}-{}", self.sha256, self.file_name)
+    }
+}
+
+/// Entries ordered from least to most recently used.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheIndex {
+    entries: Vec<CacheEntry>,
+}
+
+/// Size-capped archive cache with least-recently-used eviction.
+pub struct ArchiveCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    index: CacheIndex,
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Hex-encoded SHA-256 of a file's contents.
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        context.update(&buffer[..read]);
+    }
+    Ok(hex_digest(context))
+}
+
+/// Lowercase hex of the digest computed by `context`.
+pub fn hex_digest(context: ring::digest::Context) -> String {
+    context
+        .finish()
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+impl ArchiveCache {
+    /// `archives` in the cache directory, `$XDG_CACHE_HOME/com.github.Mar0xy.ProtonUpGtk` by default.
+    pub fn default_dir() -> Result<PathBuf> {
+        Ok(super::paths::location().cache_dir()?.join("archives"))
+    }
+
+    pub fn open(dir: &Path, max_bytes: u64) -> Result<Self> {
+        std::fs::create_dir_all(dir)?;
+
+        // A missing or corrupt index just means an empty cache
+        let mut index: CacheIndex = std::fs::read_to_string(dir.join(INDEX_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        index.entries.retain(|entry| {
+            std::fs::metadata(dir.join(entry.stored_name()))
+                .map(|m| m.len() == entry.size)
+                .unwrap_or(false)
+        });
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            max_bytes,
+            index,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn set_max_bytes(&mut self, max_bytes: u64) -> Result<()> {
+        self.max_bytes = max_bytes;
+        self.evict(None)?;
+        self.save()
+    }
+
+    pub fn total_size(&self) -> u64 {
+        // Entries sharing a checksum share a file, count it once
+        let mut seen = std::collections::HashSet::new();
+        self.index.entries.iter()
+            .filter(|entry| seen.insert(entry.stored_name()))
+            .map(|entry| entry.size)
+            .sum()
+    }
+
+    /// Path of the cached archive for `url`, marking it as recently used. If a
+    /// checksum is given, only an entry with that SHA-256 matches.
+    pub fn lookup(&mut self, url: &str, sha256: Option<&str>) -> Option<PathBuf> {
+        let position = self.index.entries.iter().position(|entry| {
+            entry.url == url && sha256.is_none_or(|sum| entry.sha256.eq_ignore_ascii_case(sum))
+        })?;
+
+        let mut entry = self.index.entries.remove(position);
+        let path = self.dir.join(entry.stored_name());
+        if !path.is_file() {
+            let _ = self.save();
+            return None;
+        }
+
+        entry.last_used = now();
+        self.index.entries.push(entry);
+        let _ = self.save();
+        Some(path)
+    }
+
+    /// Moves a finished download into the cache and returns its cached path.
+    pub fn insert(&mut self, url: &str, downloaded: &Path) -> Result<PathBuf> {
+        let file_name = downloaded
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.trim_end_matches(".part").to_string())
+            .ok_or_else(|| Error::Other("Invalid archive file name".to_string()))?;
+        let entry = CacheEntry {
+            url: url.to_string(),
+            sha256: sha256_file(downloaded)?,
+            file_name,
+            size: std::fs::metadata(downloaded)?.len(),
+            last_used: now(),
+        };
+
+        let stored = self.dir.join(entry.stored_name());
+        if stored.is_file() {
+            std::fs::remove_file(downloaded)?;
+        } else if std::fs::rename(downloaded, &stored).is_err() {
+            std::fs::copy(downloaded, &stored)?;
+            std::fs::remove_file(downloaded)?;
+        }
+
+        let (replaced, kept) = std::mem::take(&mut self.index.entries)
+            .into_iter()
+            .partition(|existing| existing.url == url);
+        self.index.entries = kept;
+        let stored_name = entry.stored_name();
+        self.index.entries.push(entry);
+        // An earlier download of the same URL is only kept if other entries share it
+        for old in replaced {
+            let old_name = old.stored_name();
+            if !self.index.entries.iter().any(|e| e.stored_name() == old_name) {
+                let _ = std::fs::remove_file(self.dir.join(old_name));
+            }
+        }
+        self.evict(Some(&stored_name))?;
+        self.save()?;
+        Ok(stored)
+    }
+
+    /// Drops least recently used entries until the cache fits its size cap.
+    /// The entry named `keep` survives even if it alone exceeds the cap.
+    fn evict(&mut self, keep: Option<&str>) -> Result<()> {
+        while self.total_size() > self.max_bytes {
+            let Some(position) = self.index.entries.iter()
+                .position(|entry| Some(entry.stored_name().as_str()) != keep)
+            else {
+                break;
+            };
+            let entry = self.index.entries.remove(position);
+            let stored_name = entry.stored_name();
+            if !self.index.entries.iter().any(|e| e.stored_name() == stored_name) {
+                let _ = std::fs::remove_file(self.dir.join(stored_name));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn clear(&mut self) -> Result<()> {
+        for entry in self.index.entries.drain(..) {
+            let _ = std::fs::remove_file(self.dir.join(entry.stored_name()));
+        }
+        // Leftovers of interrupted downloads
+        for entry in std::fs::read_dir(&self.dir)?.flatten() {
+            if entry.path().extension().is_some_and(|ext| ext == "part") {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        let contents = serde_json::to_string_pretty(&self.index)?;
+        std::fs::write(self.dir.join(INDEX_FILE), contents)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::test_util::scratch_dir;
+
+    fn download(cache: &ArchiveCache, name: &str, contents: &[u8]) -> PathBuf {
+        let path = cache.dir().join(format!("{}.part", name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_sha256_file() {
+        let root = scratch_dir("cache-sha");
+        std::fs::write(root.join("abc"), b"abc").unwrap();
+        assert_eq!(
+            sha256_file(&root.join("abc")).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_insert_and_lookup() {
+        let root = scratch_dir("cache-lookup");
+        let mut cache = ArchiveCache::open(&root, 1024).unwrap();
+        let url = "https://example.com/GE-Proton9-20.tar.gz";
+
+        let downloaded = download(&cache, "GE-Proton9-20.tar.gz", b"archive");
+        let stored = cache.insert(url, &downloaded).unwrap();
+        assert!(!downloaded.exists());
+        assert!(stored.to_str().unwrap().ends_with("-GE-Proton9-20.tar.gz"));
+
+        assert_eq!(cache.lookup(url, None), Some(stored.clone()));
+        let sha = cache.index.entries[0].sha256.clone();
+        assert_eq!(cache.lookup(url, Some(&sha.to_uppercase())), Some(stored.clone()));
+        assert_eq!(cache.lookup(url, Some("0000")), None);
+        assert_eq!(cache.lookup("https://example.com/other.tar.gz", None), None);
+
+        // The index survives reopening
+        let mut reopened = ArchiveCache::open(&root, 1024).unwrap();
+        assert_eq!(reopened.lookup(url, None), Some(stored.clone()));
+
+        // Entries whose file vanished are dropped
+        std::fs::remove_file(&stored).unwrap();
+        let mut reopened = ArchiveCache::open(&root, 1024).unwrap();
+        assert_eq!(reopened.lookup(url, None), None);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let root = scratch_dir("cache-evict");
+        let mut cache = ArchiveCache::open(&root, 20).unwrap();
+
+        let a = download(&cache, "a.tar.gz", &[b'a'; 8]);
+        let a = cache.insert("https://example.com/a.tar.gz", &a).unwrap();
+        let b = download(&cache, "b.tar.gz", &[b'b'; 8]);
+        let b = cache.insert("https://example.com/b.tar.gz", &b).unwrap();
+
+        // Using "a" makes "b" the least recently used entry
+        assert!(cache.lookup("https://example.com/a.tar.gz", None).is_some());
+        let c = download(&cache, "c.tar.gz", &[b'c'; 8]);
+        let c = cache.insert("https://example.com/c.tar.gz", &c).unwrap();
+
+        assert!(a.exists());
+        assert!(!b.exists());
+        assert!(c.exists());
+        assert_eq!(cache.total_size(), 16);
+
+        // An archive larger than the cap is kept until something else is inserted
+        let big = download(&cache, "big.tar.gz", &[b'x'; 32]);
+        let big = cache.insert("https://example.com/big.tar.gz", &big).unwrap();
+        assert!(big.exists());
+        assert_eq!(cache.index.entries.len(), 1);
+
+        cache.clear().unwrap();
+        assert!(!big.exists());
+        assert_eq!(cache.total_size(), 0);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_reinsert_replaces_file() {
+        let root = scratch_dir("cache-reinsert");
+        let mut cache = ArchiveCache::open(&root, 1024).unwrap();
+        let url = "https://example.com/GE-Proton9-20.tar.gz";
+
+        let old = download(&cache, "GE-Proton9-20.tar.gz", b"first build");
+        let old = cache.insert(url, &old).unwrap();
+        let new = download(&cache, "GE-Proton9-20.tar.gz", b"second build");
+        let new = cache.insert(url, &new).unwrap();
+
+        assert!(!old.exists());
+        assert!(new.exists());
+        assert_eq!(cache.index.entries.len(), 1);
+        assert_eq!(cache.total_size(), 12);
+
+        // Identical bytes keep their file
+        let same = download(&cache, "GE-Proton9-20.tar.gz", b"second build");
+        assert_eq!(cache.insert(url, &same).unwrap(), new);
+        assert!(new.exists());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_identical_content_shares_file() {
+        let root = scratch_dir("cache-shared");
+        let mut cache = ArchiveCache::open(&root, 1024).unwrap();
+
+        let first = download(&cache, "tool.tar.xz", b"same");
+        let first = cache.insert("https://mirror-a.example.com/tool.tar.xz", &first).unwrap();
+        let second = download(&cache, "tool.tar.xz", b"same");
+        let second = cache.insert("https://mirror-b.example.com/tool.tar.xz", &second).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(cache.index.entries.len(), 2);
+        assert_eq!(cache.total_size(), 4);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
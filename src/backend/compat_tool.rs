@@ -0,0 +1,287 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::vdf::{self, VdfValue};
+use super::error::{Error, Result};
+
+/// One entry of the `compat_tools` block in `compatibilitytool.vdf`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompatToolEntry {
+    /// Key Steam uses to identify the tool; must be unique across all tools
+    pub internal_name: String,
+    pub display_name: Option<String>,
+    pub install_path: Option<String>,
+    pub from_oslist: Vec<String>,
+    pub to_oslist: Vec<String>,
+}
+
+/// Contents of `toolmanifest.vdf`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolManifest {
+    pub version: Option<String>,
+    pub commandline: Option<String>,
+    pub require_tool_appid: Option<String>,
+    pub use_sessions: bool,
+    pub compatmanager_layer_name: Option<String>,
+}
+
+/// Metadata of a Steam compatibility tool directory.
+#[derive(Debug, Clone)]
+pub struct CompatToolInfo {
+    pub dir: PathBuf,
+    pub entries: Vec<CompatToolEntry>,
+    pub manifest: Option<ToolManifest>,
+}
+
+fn string_field(value: &VdfValue, key: &str) -> Option<String> {
+    value.get(key).and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
+fn os_list(value: &VdfValue, key: &str) -> Vec<String> {
+    string_field(value, key)
+        .map(|list| {
+            list.split(',')
+                .map(|os| os.trim().to_string())
+                .filter(|os| !os.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn parse_compatibilitytool_vdf(contents: &str) -> Result<Vec<CompatToolEntry>> {
+    let root = vdf::parse(contents)?;
+    let tools = root
+        .get("compatibilitytools")
+        .and_then(|v| v.get("compat_tools"))
+        .ok_or_else(|| Error::Other("compatibilitytool.vdf has no compat_tools section".to_string()))?;
+
+    Ok(tools
+        .entries()
+        .iter()
+        .map(|(internal_name, tool)| CompatToolEntry {
+            internal_name: internal_name.clone(),
+            display_name: string_field(tool, "display_name"),
+            install_path: string_field(tool, "install_path"),
+            from_oslist: os_list(tool, "from_oslist"),
+            to_oslist: os_list(tool, "to_oslist"),
+        })
+        .collect())
+}
+
+pub fn parse_toolmanifest_vdf(contents: &str) -> Result<ToolManifest> {
+    let root = vdf::parse(contents)?;
+    let manifest = root
+        .get("manifest")
+        .ok_or_else(|| Error::Other("toolmanifest.vdf has no manifest section".to_string()))?;
+
+    Ok(ToolManifest {
+        version: string_field(manifest, "version"),
+        commandline: string_field(manifest, "commandline"),
+        require_tool_appid: string_field(manifest, "require_tool_appid"),
+        use_sessions: string_field(manifest, "use_sessions").as_deref() == Some("1"),
+        compatmanager_layer_name: string_field(manifest, "compatmanager_layer_name"),
+    })
+}
+
+impl CompatToolInfo {
+    /// Reads the metadata files of a tool directory. Fails if `compatibilitytool.vdf`
+    /// is missing or malformed; a missing `toolmanifest.vdf` is reported as `None`.
+    pub fn read(dir: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(dir.join("compatibilitytool.vdf"))?;
+        let entries = parse_compatibilitytool_vdf(&contents)?;
+
+        let manifest = match std::fs::read_to_string(dir.join("toolmanifest.vdf")) {
+            Ok(contents) => Some(parse_toolmanifest_vdf(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            entries,
+            manifest,
+        })
+    }
+
+    /// Name shown in Steam's compatibility tool list.
+    pub fn display_name(&self) -> Option<&str> {
+        let entry = self.entries.first()?;
+        Some(entry.display_name.as_deref().unwrap_or(&entry.internal_name))
+    }
+
+    /// Internal and display names, for matching a version against this tool.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().flat_map(|entry| {
+            std::iter::once(entry.internal_name.as_str()).chain(entry.display_name.as_deref())
+        })
+    }
+
+    /// Checks that Steam would list this tool as a Windows-on-Linux runner.
+    pub fn check_usable(&self) -> Result<()> {
+        if self.entries.is_empty() {
+            return Err(Error::Other("compatibilitytool.vdf declares no tools".to_string()));
+        }
+        for entry in &self.entries {
+            if !entry.to_oslist.iter().any(|os| os == "linux") {
+                return Err(Error::Other(format!(
+                    "Tool '{}' does not target linux in compatibilitytool.vdf",
+                    entry.internal_name
+                )));
+            }
+        }
+
+        let manifest = self
+            .manifest
+            .as_ref()
+            .ok_or_else(|| Error::Other("toolmanifest.vdf is missing".to_string()))?;
+        if manifest.commandline.as_deref().unwrap_or("").is_empty() {
+            return Err(Error::Other("toolmanifest.vdf has no commandline".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads every tool directory under a Steam `compatibilitytools.d`. Directories
+/// without readable metadata are skipped.
+pub fn scan_compat_tools(steam_path: &Path) -> Vec<CompatToolInfo> {
+    let Ok(entries) = std::fs::read_dir(steam_path) else {
+        return Vec::new();
+    };
+
+    let mut tools: Vec<CompatToolInfo> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter_map(|path| CompatToolInfo::read(&path).ok())
+        .collect();
+    tools.sort_by(|a, b| a.dir.cmp(&b.dir));
+    tools
+}
+
+/// Internal names declared by more than one tool. Steam only loads one of them
+/// and silently ignores the rest.
+pub fn duplicate_internal_names(tools: &[CompatToolInfo]) -> BTreeMap<String, Vec<PathBuf>> {
+    let mut by_name: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for tool in tools {
+        for entry in &tool.entries {
+            by_name.entry(entry.internal_name.clone()).or_default().push(tool.dir.clone());
+        }
+    }
+    by_name.retain(|_, dirs| dirs.len() > 1);
+    by_name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::test_util::scratch_dir;
+
+    const COMPATIBILITYTOOL_VDF: &str = r#"
+"compatibilitytools"
+{
+  "compat_tools"
+  {
+    "GE-Proton9-20" // Internal name of this tool
+    {
+      "install_path" "."
+      "display_name" "GE-Proton 9-20"
+      "from_oslist"  "windows"
+      "to_oslist"    "linux"
+    }
+  }
+}
+"#;
+
+    const TOOLMANIFEST_VDF: &str = r#"
+"manifest"
+{
+  "version" "2"
+  "commandline" "/proton %verb%"
+  "require_tool_appid" "1628350"
+  "use_sessions" "1"
+  "compatmanager_layer_name" "proton"
+}
+"#;
+
+    fn write_tool(dir: &Path, compatibilitytool: &str, toolmanifest: Option<&str>) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(dir.join("compatibilitytool.vdf"), compatibilitytool).unwrap();
+        if let Some(toolmanifest) = toolmanifest {
+            std::fs::write(dir.join("toolmanifest.vdf"), toolmanifest).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_names_and_display_name() {
+        let contents = r#""compatibilitytools" { "compat_tools" {
+            "GE-Proton9-20" { "display_name" "GE-Proton 9-20" "to_oslist" "linux" }
+            "GE-Proton9-20-debug" { "to_oslist" "linux" }
+        } }"#;
+        let info = CompatToolInfo {
+            dir: PathBuf::from("GE-Proton9-20"),
+            entries: parse_compatibilitytool_vdf(contents).unwrap(),
+            manifest: None,
+        };
+        assert_eq!(info.display_name(), Some("GE-Proton 9-20"));
+        assert_eq!(
+            info.names().collect::<Vec<_>>(),
+            vec!["GE-Proton9-20", "GE-Proton 9-20", "GE-Proton9-20-debug"]
+        );
+
+        // Without a display name Steam lists the internal name
+        let info = CompatToolInfo { entries: info.entries[1..].to_vec(), ..info };
+        assert_eq!(info.display_name(), Some("GE-Proton9-20-debug"));
+        assert!(parse_compatibilitytool_vdf("\"other\" {}").is_err());
+    }
+
+    #[test]
+    fn test_parse_toolmanifest_vdf() {
+        let manifest = parse_toolmanifest_vdf(TOOLMANIFEST_VDF).unwrap();
+        assert_eq!(manifest.version.as_deref(), Some("2"));
+        assert_eq!(manifest.commandline.as_deref(), Some("/proton %verb%"));
+        assert_eq!(manifest.require_tool_appid.as_deref(), Some("1628350"));
+        assert!(manifest.use_sessions);
+        assert_eq!(manifest.compatmanager_layer_name.as_deref(), Some("proton"));
+        assert!(parse_toolmanifest_vdf("\"other\" {}").is_err());
+    }
+
+    #[test]
+    fn test_read_and_check_usable() {
+        let root = scratch_dir("compat-read");
+        write_tool(&root.join("good"), COMPATIBILITYTOOL_VDF, Some(TOOLMANIFEST_VDF));
+        write_tool(&root.join("no-manifest"), COMPATIBILITYTOOL_VDF, None);
+
+        let good = CompatToolInfo::read(&root.join("good")).unwrap();
+        assert_eq!(good.display_name(), Some("GE-Proton 9-20"));
+        assert_eq!(good.names().collect::<Vec<_>>(), vec!["GE-Proton9-20", "GE-Proton 9-20"]);
+        assert!(good.check_usable().is_ok());
+
+        let no_manifest = CompatToolInfo::read(&root.join("no-manifest")).unwrap();
+        assert!(no_manifest.manifest.is_none());
+        assert!(no_manifest.check_usable().is_err());
+
+        assert!(CompatToolInfo::read(&root.join("missing")).is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_scan_and_duplicates() {
+        let root = scratch_dir("compat-scan");
+        write_tool(&root.join("GE-Proton9-20"), COMPATIBILITYTOOL_VDF, Some(TOOLMANIFEST_VDF));
+        write_tool(&root.join("GE-Proton9-20-copy"), COMPATIBILITYTOOL_VDF, Some(TOOLMANIFEST_VDF));
+        write_tool(&root.join("broken"), "\"compatibilitytools\" {", None);
+        std::fs::create_dir_all(root.join("empty")).unwrap();
+
+        let tools = scan_compat_tools(&root);
+        assert_eq!(tools.len(), 2);
+
+        let duplicates = duplicate_internal_names(&tools);
+        assert_eq!(
+            duplicates.get("GE-Proton9-20"),
+            Some(&vec![root.join("GE-Proton9-20"), root.join("GE-Proton9-20-copy")])
+        );
+        assert_eq!(duplicates.len(), 1);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
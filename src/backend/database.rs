@@ -1,47 +1,270 @@
-use anyhow::Result;
 use rusqlite::{Connection, params};
-use std::path::PathBuf;
-use super::Launcher;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use super::network::NetworkSettings;
+use super::paths;
+use super::version::Version;
+use super::{Launcher, ReleaseChannel};
+use super::error::{Error, Result};
 
+/// Archives for a handful of Proton builds fit in the default cache size.
+pub const DEFAULT_ARCHIVE_CACHE_LIMIT_MB: u64 = 4096;
+
+/// Where an installed runner came from, so it can be exported and reinstalled elsewhere.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunnerSource {
+    pub tool_name: Option<String>,
+    pub url: Option<String>,
+    /// SHA-256 of the archive the runner was extracted from
+    pub sha256: Option<String>,
+}
+
+/// A row of the `installed_runners` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledRunner {
+    pub version: String,
+    pub launcher: Launcher,
+    /// Absolute directory the runner was installed to; identifies the row
+    pub install_dir: PathBuf,
+    pub source: RunnerSource,
+    /// Naming scheme of the version, see [`super::version::VersionFamily::as_str`]
+    pub tool_family: Option<String>,
+    /// Size of the installed directory in bytes
+    pub installed_size: Option<u64>,
+    /// Unix timestamp of the installation
+    pub installed_at: Option<u64>,
+    /// Version of ProtonUp-GTK that installed the runner
+    pub app_version: Option<String>,
+}
+
+impl InstalledRunner {
+    /// Record for a runner installed now by this build. The size is left for the caller to fill in.
+    pub fn new(version: &str, launcher: &Launcher, install_dir: &Path, source: &RunnerSource) -> Self {
+        let installed_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Self {
+            version: version.to_string(),
+            launcher: launcher.clone(),
+            install_dir: std::path::absolute(install_dir).unwrap_or_else(|_| install_dir.to_path_buf()),
+            source: source.clone(),
+            tool_family: Version::parse(version).map(|v| v.family.as_str().to_string()),
+            installed_size: None,
+            installed_at: Some(installed_at),
+            app_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        }
+    }
+}
+
+/// Schema upgrades in order. Running entry `n` moves a database from
+/// `user_version` n to n + 1.
+const MIGRATIONS: &[fn(&Connection) -> Result<()>] = &[
+    migrate_base_schema,
+    migrate_install_metadata,
+];
+
+/// Tables as they were before the schema was versioned, including the columns
+/// older builds added on the fly.
+fn migrate_base_schema(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )?;
+    
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS installed_runners (
+            version TEXT NOT NULL,
+            launcher TEXT NOT NULL,
+            install_dir TEXT,
+            PRIMARY KEY (version, launcher)
+        )",
+        [],
+    )?;
+    
+    for column in ["install_dir", "tool_name", "source_url", "sha256"] {
+        let exists = conn
+            .prepare("SELECT 1 FROM pragma_table_info('installed_runners') WHERE name = ?")?
+            .exists(params![column])?;
+        if !exists {
+            conn.execute(&format!("ALTER TABLE installed_runners ADD COLUMN {} TEXT", column), [])?;
+        }
+    }
+    Ok(())
+}
+
+/// Keys runners by install directory so the same version can live under two
+/// custom paths, and adds the audit columns. Rows written before directories
+/// were recorded get `<launcher path>/<version>`; a row whose directory cannot
+/// be derived keeps a NULL `install_dir` and is left out of listings.
+fn migrate_install_metadata(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE installed_runners_new (
+            install_dir TEXT PRIMARY KEY,
+            version TEXT NOT NULL,
+            launcher TEXT NOT NULL,
+            tool_name TEXT,
+            tool_family TEXT,
+            source_url TEXT,
+            sha256 TEXT,
+            installed_size INTEGER,
+            installed_at INTEGER,
+            app_version TEXT
+        );
+        INSERT INTO installed_runners_new (install_dir, version, launcher, tool_name, source_url, sha256)
+            SELECT install_dir, version, launcher, tool_name, source_url, sha256
+            FROM installed_runners WHERE install_dir IS NOT NULL;",
+    )?;
+    
+    let mut stmt = conn.prepare(
+        "SELECT version, launcher, tool_name, source_url, sha256 FROM installed_runners WHERE install_dir IS NULL",
+    )?;
+    let legacy = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, Option<String>>(4)?,
+        ))
+    })?;
+    for row in legacy {
+        let (version, launcher, tool_name, source_url, sha256) = row?;
+        let install_dir = legacy_install_dir(conn, &launcher, &version)?;
+        // A row already recorded at the derived directory wins
+        conn.execute(
+            "INSERT OR IGNORE INTO installed_runners_new (install_dir, version, launcher, tool_name, source_url, sha256)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            params![
+                install_dir.map(|dir| dir.to_string_lossy().into_owned()),
+                version,
+                launcher,
+                tool_name,
+                source_url,
+                sha256
+            ],
+        )?;
+    }
+    drop(stmt);
+    
+    conn.execute_batch(
+        "DROP TABLE installed_runners;
+        ALTER TABLE installed_runners_new RENAME TO installed_runners;
+        CREATE INDEX installed_runners_version ON installed_runners (version, launcher);",
+    )?;
+    Ok(())
+}
+
+/// Where a runner recorded without a directory was installed: the launcher's
+/// configured path, or its default under the home directory.
+fn legacy_install_dir(conn: &Connection, launcher: &str, version: &str) -> Result<Option<PathBuf>> {
+    let key = format!("{}_path", launcher.to_lowercase());
+    let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = ?")?;
+    let mut rows = stmt.query(params![key])?;
+    
+    let base = if let Some(row) = rows.next()? {
+        Some(PathBuf::from(row.get::<_, String>(0)?))
+    } else {
+        launcher
+            .parse::<Launcher>()
+            .ok()
+            .zip(dirs::home_dir())
+            .map(|(launcher, home)| home.join(launcher.default_relative_path()))
+    };
+    Ok(base.map(|base| base.join(version)))
+}
+
+/// `settings.db` at schema version 2 is backed up as `settings.db.v2.bak`.
+fn backup_path(db_path: &Path, version: usize) -> PathBuf {
+    let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{}.bak", version));
+    db_path.with_file_name(name)
+}
+
+/// Handle to the settings database. Clones share one connection, so a single
+/// handle opened at startup can be passed to the UI and to async tasks alike.
+#[derive(Clone)]
 pub struct Database {
-    conn: Connection,
+    conn: Arc<Mutex<Connection>>,
 }
 
 impl Database {
     pub fn new() -> Result<Self> {
-        let config_dir = dirs::config_dir()
-            .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?
-            .join("com.github.Mar0xy.ProtonUpGTK");
+        let config_dir = paths::location().config_dir()?;
         
         std::fs::create_dir_all(&config_dir)?;
-        let db_path = config_dir.join("settings.db");
+        Self::open(&config_dir.join("settings.db"))
+    }
+    
+    pub fn open(db_path: &Path) -> Result<Self> {
+        let mut conn = Connection::open(db_path)?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        Self::migrate(&mut conn, db_path)?;
         
-        let conn = Connection::open(db_path)?;
+        // Readers no longer block the writer, e.g. a second window or instance
+        let journal_mode: String = conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get(0))?;
+        if !journal_mode.eq_ignore_ascii_case("wal") && !journal_mode.eq_ignore_ascii_case("memory") {
+            return Err(Error::Other(format!("Could not enable WAL mode for {}", db_path.display())));
+        }
         
-        // Create tables if they don't exist
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS settings (
-                key TEXT PRIMARY KEY,
-                value TEXT NOT NULL
-            )",
-            [],
-        )?;
+        Ok(Self { conn: Arc::new(Mutex::new(conn)) })
+    }
+    
+    /// The shared connection. Statements are short, so the lock is never held for long;
+    /// a panic on another thread does not leave the connection unusable.
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+    
+    /// Applies the migrations the database has not seen yet, each in its own
+    /// transaction. An existing database is copied to `<name>.v<version>.bak`
+    /// first so a failed or unwanted upgrade can be undone by hand.
+    fn migrate(conn: &mut Connection, db_path: &Path) -> Result<()> {
+        let current: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
         
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS installed_runners (
-                version TEXT NOT NULL,
-                launcher TEXT NOT NULL,
-                PRIMARY KEY (version, launcher)
-            )",
-            [],
-        )?;
+        if current > MIGRATIONS.len() {
+            return Err(Error::Other(format!(
+                "Settings database {} has schema version {}, but this version of ProtonUp-GTK only supports up to {}. Please update ProtonUp-GTK.",
+                db_path.display(),
+                current,
+                MIGRATIONS.len()
+            )));
+        }
+        if current == MIGRATIONS.len() {
+            return Ok(());
+        }
         
-        Ok(Self { conn })
+        // A brand-new database has nothing worth backing up
+        let has_tables = conn
+            .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table'")?
+            .exists([])?;
+        if has_tables {
+            let backup = backup_path(db_path, current);
+            if backup.exists() {
+                std::fs::remove_file(&backup)?;
+            }
+            conn.execute("VACUUM INTO ?", params![backup.to_string_lossy()])?;
+        }
+        
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(current) {
+            let tx = conn.transaction()?;
+            migration(&tx)
+                .map_err(|e| Error::Other(format!("Settings database migration to version {} failed: {}", index + 1, e)))?;
+            tx.pragma_update(None, "user_version", index + 1)?;
+            tx.commit()?;
+        }
+        Ok(())
     }
     
     // Settings methods
     pub fn get_setting(&self, key: &str) -> Result<Option<String>> {
-        let mut stmt = self.conn.prepare("SELECT value FROM settings WHERE key = ?")?;
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = ?")?;
         let mut rows = stmt.query(params![key])?;
         
         if let Some(row) = rows.next()? {
@@ -52,7 +275,7 @@ impl Database {
     }
     
     pub fn set_setting(&self, key: &str, value: &str) -> Result<()> {
-        self.conn.execute(
+        self.conn().execute(
             "INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)",
             params![key, value],
         )?;
@@ -60,87 +283,368 @@ impl Database {
     }
     
     pub fn delete_setting(&self, key: &str) -> Result<()> {
-        self.conn.execute("DELETE FROM settings WHERE key = ?", params![key])?;
+        self.conn().execute("DELETE FROM settings WHERE key = ?", params![key])?;
         Ok(())
     }
     
     // Path methods
-    pub fn get_steam_path(&self) -> Result<Option<PathBuf>> {
-        if let Some(path_str) = self.get_setting("steam_path")? {
-            Ok(Some(PathBuf::from(path_str)))
-        } else {
-            Ok(None)
-        }
+    pub fn get_launcher_path(&self, launcher: &Launcher) -> Result<Option<PathBuf>> {
+        let key = format!("{}_path", launcher.to_string().to_lowercase());
+        Ok(self.get_setting(&key)?.map(PathBuf::from))
     }
     
-    pub fn set_steam_path(&self, path: Option<&PathBuf>) -> Result<()> {
+    pub fn set_launcher_path(&self, launcher: &Launcher, path: Option<&PathBuf>) -> Result<()> {
+        let key = format!("{}_path", launcher.to_string().to_lowercase());
         if let Some(p) = path {
             if let Some(path_str) = p.to_str() {
-                self.set_setting("steam_path", path_str)?;
+                self.set_setting(&key, path_str)?;
             }
         } else {
-            self.delete_setting("steam_path")?;
+            self.delete_setting(&key)?;
         }
         Ok(())
     }
     
-    pub fn get_lutris_path(&self) -> Result<Option<PathBuf>> {
-        if let Some(path_str) = self.get_setting("lutris_path")? {
-            Ok(Some(PathBuf::from(path_str)))
+    pub fn set_steam_path(&self, path: Option<&PathBuf>) -> Result<()> {
+        self.set_launcher_path(&Launcher::Steam, path)
+    }
+    
+    pub fn set_lutris_path(&self, path: Option<&PathBuf>) -> Result<()> {
+        self.set_launcher_path(&Launcher::Lutris, path)
+    }
+    
+    // Release channel methods
+    pub fn get_release_channel(&self, tool_name: &str) -> Result<ReleaseChannel> {
+        let key = format!("channel_{}", tool_name);
+        Ok(self.get_setting(&key)?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_default())
+    }
+    
+    pub fn set_release_channel(&self, tool_name: &str, channel: ReleaseChannel) -> Result<()> {
+        let key = format!("channel_{}", tool_name);
+        self.set_setting(&key, channel.as_str())
+    }
+    
+    // Mirror methods
+    /// Mirror URL templates for `tool_name`, in the order they are tried.
+    pub fn get_mirrors(&self, tool_name: &str) -> Result<Vec<String>> {
+        let key = format!("mirrors_{}", tool_name);
+        Ok(self.get_setting(&key)?
+            .map(|value| value.lines().map(str::to_string).collect())
+            .unwrap_or_default())
+    }
+    
+    pub fn set_mirrors(&self, tool_name: &str, templates: &[String]) -> Result<()> {
+        let key = format!("mirrors_{}", tool_name);
+        let templates: Vec<&str> = templates.iter()
+            .map(|template| template.trim())
+            .filter(|template| !template.is_empty())
+            .collect();
+        if templates.is_empty() {
+            self.delete_setting(&key)
         } else {
-            Ok(None)
+            self.set_setting(&key, &templates.join("\n"))
         }
     }
     
-    pub fn set_lutris_path(&self, path: Option<&PathBuf>) -> Result<()> {
-        if let Some(p) = path {
-            if let Some(path_str) = p.to_str() {
-                self.set_setting("lutris_path", path_str)?;
+    pub fn get_probe_mirrors(&self) -> Result<bool> {
+        Ok(self.get_setting("probe_mirrors")?.as_deref() == Some("true"))
+    }
+    
+    pub fn set_probe_mirrors(&self, enabled: bool) -> Result<()> {
+        self.set_setting("probe_mirrors", if enabled { "true" } else { "false" })
+    }
+    
+    // Archive cache methods
+    pub fn get_archive_cache_enabled(&self) -> Result<bool> {
+        Ok(self.get_setting("archive_cache_enabled")?.as_deref() != Some("false"))
+    }
+    
+    pub fn set_archive_cache_enabled(&self, enabled: bool) -> Result<()> {
+        self.set_setting("archive_cache_enabled", if enabled { "true" } else { "false" })
+    }
+    
+    pub fn get_archive_cache_limit_mb(&self) -> Result<u64> {
+        Ok(self.get_setting("archive_cache_limit_mb")?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_ARCHIVE_CACHE_LIMIT_MB))
+    }
+    
+    pub fn set_archive_cache_limit_mb(&self, limit_mb: u64) -> Result<()> {
+        self.set_setting("archive_cache_limit_mb", &limit_mb.to_string())
+    }
+    
+    pub fn get_streaming_extraction(&self) -> Result<bool> {
+        Ok(self.get_setting("streaming_extraction")?.as_deref() == Some("true"))
+    }
+    
+    pub fn set_streaming_extraction(&self, enabled: bool) -> Result<()> {
+        self.set_setting("streaming_extraction", if enabled { "true" } else { "false" })
+    }
+    
+    // Network methods
+    pub fn get_network_settings(&self) -> Result<NetworkSettings> {
+        let defaults = NetworkSettings::default();
+        let secs = |key: &str, default: Duration| -> Result<Duration> {
+            Ok(self.get_setting(key)?
+                .and_then(|value| value.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(default))
+        };
+        Ok(NetworkSettings {
+            connect_timeout: secs("connect_timeout_secs", defaults.connect_timeout)?,
+            read_timeout: secs("read_timeout_secs", defaults.read_timeout)?,
+            max_attempts: self.get_setting("download_attempts")?
+                .and_then(|value| value.parse().ok())
+                .filter(|attempts| *attempts > 0)
+                .unwrap_or(defaults.max_attempts),
+            proxy: self.get_setting("proxy_url")?,
+            no_proxy: self.get_setting("no_proxy")?,
+            ca_bundle: self.get_setting("ca_bundle_path")?.map(PathBuf::from),
+            accept_invalid_certs: self.get_setting("accept_invalid_certs")?.as_deref() == Some("true"),
+            ..defaults
+        })
+    }
+    
+    pub fn set_network_settings(&self, settings: &NetworkSettings) -> Result<()> {
+        self.set_setting("connect_timeout_secs", &settings.connect_timeout.as_secs().to_string())?;
+        self.set_setting("read_timeout_secs", &settings.read_timeout.as_secs().to_string())?;
+        self.set_setting("download_attempts", &settings.max_attempts.to_string())?;
+        let optional = [
+            ("proxy_url", settings.proxy.as_deref().filter(|url| !url.trim().is_empty())),
+            ("no_proxy", settings.no_proxy.as_deref().filter(|hosts| !hosts.trim().is_empty())),
+            ("ca_bundle_path", settings.ca_bundle.as_deref().and_then(|path| path.to_str())),
+        ];
+        for (key, value) in optional {
+            match value {
+                Some(value) => self.set_setting(key, value)?,
+                None => self.delete_setting(key)?,
             }
-        } else {
-            self.delete_setting("lutris_path")?;
         }
-        Ok(())
+        self.set_setting("accept_invalid_certs", if settings.accept_invalid_certs { "true" } else { "false" })
+    }
+    
+    /// Download speed limit in KiB/s, `None` for unlimited.
+    pub fn get_download_rate_limit_kib(&self) -> Result<Option<u64>> {
+        Ok(self.get_setting("download_rate_limit_kib")?
+            .and_then(|value| value.parse().ok())
+            .filter(|limit| *limit > 0))
+    }
+    
+    pub fn set_download_rate_limit_kib(&self, limit: Option<u64>) -> Result<()> {
+        match limit.filter(|limit| *limit > 0) {
+            Some(limit) => self.set_setting("download_rate_limit_kib", &limit.to_string()),
+            None => self.delete_setting("download_rate_limit_kib"),
+        }
+    }
+    
+    // Temporary directory methods
+    pub fn get_temp_dir(&self) -> Result<Option<PathBuf>> {
+        Ok(self.get_setting("temp_dir")?.map(PathBuf::from))
+    }
+    
+    pub fn set_temp_dir(&self, dir: Option<&Path>) -> Result<()> {
+        match dir.and_then(|dir| dir.to_str()) {
+            Some(dir) => self.set_setting("temp_dir", dir),
+            None => self.delete_setting("temp_dir"),
+        }
     }
     
     // Installed runners methods
-    pub fn add_installed_runner(&self, version: &str, launcher: &Launcher) -> Result<()> {
-        let launcher_str = match launcher {
-            Launcher::Steam => "Steam",
-            Launcher::Lutris => "Lutris",
-        };
-        
-        self.conn.execute(
-            "INSERT OR REPLACE INTO installed_runners (version, launcher) VALUES (?, ?)",
-            params![version, launcher_str],
+    pub fn add_installed_runner(&self, runner: &InstalledRunner) -> Result<()> {
+        self.conn().execute(
+            "INSERT OR REPLACE INTO installed_runners
+                (install_dir, version, launcher, tool_name, tool_family, source_url, sha256,
+                 installed_size, installed_at, app_version)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                runner.install_dir.to_string_lossy(),
+                runner.version,
+                runner.launcher.to_string(),
+                runner.source.tool_name,
+                runner.tool_family,
+                runner.source.url,
+                runner.source.sha256,
+                runner.installed_size,
+                runner.installed_at,
+                runner.app_version,
+            ],
         )?;
         Ok(())
     }
     
-    pub fn remove_installed_runner(&self, version: &str, launcher: &Launcher) -> Result<()> {
-        let launcher_str = match launcher {
-            Launcher::Steam => "Steam",
-            Launcher::Lutris => "Lutris",
-        };
-        
-        self.conn.execute(
-            "DELETE FROM installed_runners WHERE version = ? AND launcher = ?",
-            params![version, launcher_str],
+    pub fn list_installed_runners(&self) -> Result<Vec<InstalledRunner>> {
+        self.query_installed_runners("SELECT * FROM installed_runners ORDER BY launcher, version", [])
+    }
+    
+    /// Directories a version was recorded at for a launcher, most recent install first.
+    pub fn get_install_dirs(&self, version: &str, launcher: &Launcher) -> Result<Vec<PathBuf>> {
+        let runners = self.query_installed_runners(
+            "SELECT * FROM installed_runners WHERE version = ? AND launcher = ?
+             ORDER BY installed_at DESC",
+            params![version, launcher.to_string()],
         )?;
-        Ok(())
+        Ok(runners.into_iter().map(|runner| runner.install_dir).collect())
     }
     
-    pub fn is_runner_installed(&self, version: &str, launcher: &Launcher) -> Result<bool> {
-        let launcher_str = match launcher {
-            Launcher::Steam => "Steam",
-            Launcher::Lutris => "Lutris",
-        };
-        
-        let mut stmt = self.conn.prepare(
-            "SELECT COUNT(*) FROM installed_runners WHERE version = ? AND launcher = ?"
+    pub fn remove_installed_runner(&self, install_dir: &Path) -> Result<()> {
+        self.conn().execute(
+            "DELETE FROM installed_runners WHERE install_dir = ?",
+            params![install_dir.to_string_lossy()],
         )?;
-        let count: i64 = stmt.query_row(params![version, launcher_str], |row| row.get(0))?;
+        Ok(())
+    }
+    
+    fn query_installed_runners<P: rusqlite::Params>(&self, sql: &str, params: P) -> Result<Vec<InstalledRunner>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params, |row| {
+            // Rows written for launchers this build does not know are skipped
+            let Ok(launcher) = row.get::<_, String>("launcher")?.parse() else {
+                return Ok(None);
+            };
+            // As are legacy rows whose directory could not be derived
+            let Some(install_dir) = row.get::<_, Option<String>>("install_dir")? else {
+                return Ok(None);
+            };
+            Ok(Some(InstalledRunner {
+                version: row.get("version")?,
+                launcher,
+                install_dir: PathBuf::from(install_dir),
+                source: RunnerSource {
+                    tool_name: row.get("tool_name")?,
+                    url: row.get("source_url")?,
+                    sha256: row.get("sha256")?,
+                },
+                tool_family: row.get("tool_family")?,
+                installed_size: row.get("installed_size")?,
+                installed_at: row.get("installed_at")?,
+                app_version: row.get("app_version")?,
+            }))
+        })?;
         
-        Ok(count > 0)
+        let mut runners = Vec::new();
+        for row in rows {
+            if let Some(runner) = row? {
+                runners.push(runner);
+            }
+        }
+        Ok(runners)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::test_util::scratch_dir;
+
+    fn user_version(path: &Path) -> usize {
+        Connection::open(path).unwrap()
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_new_database_is_current() {
+        let root = scratch_dir("db-new");
+        let path = root.join("settings.db");
+        let db = Database::open(&path).unwrap();
+        db.set_setting("key", "value").unwrap();
+        drop(db);
+
+        assert_eq!(user_version(&path), MIGRATIONS.len());
+        assert!(!backup_path(&path, 0).exists(), "Nothing to back up for a new database");
+
+        // Reopening an up-to-date database changes nothing
+        let db = Database::open(&path).unwrap();
+        assert_eq!(db.get_setting("key").unwrap().as_deref(), Some("value"));
+        assert!(!backup_path(&path, MIGRATIONS.len()).exists());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_upgrade_unversioned_database() {
+        let root = scratch_dir("db-legacy");
+        let path = root.join("settings.db");
+        {
+            // Layout written before the schema was versioned
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+                 CREATE TABLE installed_runners (version TEXT NOT NULL, launcher TEXT NOT NULL, PRIMARY KEY (version, launcher));
+                 INSERT INTO settings VALUES ('steam_path', '/games/steam');
+                 INSERT INTO installed_runners VALUES ('GE-Proton8-1', 'Steam');
+                 INSERT INTO installed_runners VALUES ('lutris-GE-Proton8-26-x86_64', 'Lutris');
+                 INSERT INTO installed_runners VALUES ('wine-9.0', 'Winery');",
+            ).unwrap();
+            migrate_base_schema(&conn).unwrap();
+            conn.execute(
+                "INSERT INTO installed_runners (version, launcher, install_dir, source_url) VALUES (?, ?, ?, ?)",
+                params!["GE-Proton9-20", "Steam", "/games/steam/GE-Proton9-20", "https://example.com/GE-Proton9-20.tar.gz"],
+            ).unwrap();
+        }
+
+        let db = Database::open(&path).unwrap();
+        assert_eq!(user_version(&path), MIGRATIONS.len());
+        assert_eq!(db.get_launcher_path(&Launcher::Steam).unwrap(), Some(PathBuf::from("/games/steam")));
+
+        // Legacy rows get their directory from the launcher path, custom or default
+        let runners = db.list_installed_runners().unwrap();
+        let dirs: Vec<_> = runners.iter().map(|runner| (runner.version.as_str(), runner.install_dir.clone())).collect();
+        let lutris_default = dirs::home_dir().unwrap().join(Launcher::Lutris.default_relative_path());
+        assert_eq!(dirs, vec![
+            ("lutris-GE-Proton8-26-x86_64", lutris_default.join("lutris-GE-Proton8-26-x86_64")),
+            ("GE-Proton8-1", PathBuf::from("/games/steam/GE-Proton8-1")),
+            ("GE-Proton9-20", PathBuf::from("/games/steam/GE-Proton9-20")),
+        ]);
+        assert_eq!(runners[2].source.url.as_deref(), Some("https://example.com/GE-Proton9-20.tar.gz"));
+        assert_eq!(db.get_install_dirs("GE-Proton8-1", &Launcher::Steam).unwrap(), vec![PathBuf::from("/games/steam/GE-Proton8-1")]);
+
+        // A row with no launcher path to derive from is kept, just not listed
+        let unresolved: Option<String> = db.conn()
+            .query_row("SELECT install_dir FROM installed_runners WHERE launcher = 'Winery'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(unresolved, None);
+
+        // The backup still has the old layout
+        let backup = backup_path(&path, 0);
+        assert_eq!(backup.file_name().unwrap(), "settings.db.v0.bak");
+        assert_eq!(user_version(&backup), 0);
+        let backup_rows: usize = Connection::open(&backup).unwrap()
+            .query_row("SELECT COUNT(*) FROM installed_runners", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(backup_rows, 4);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_shared_handle() {
+        let root = scratch_dir("db-shared");
+        let db = Database::open(&root.join("settings.db")).unwrap();
+        let journal_mode: String = db.conn().query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+        assert_eq!(journal_mode, "wal");
+
+        // Writes through a clone on another thread are visible through the original
+        let clone = db.clone();
+        std::thread::spawn(move || clone.set_setting("from_thread", "yes").unwrap())
+            .join()
+            .unwrap();
+        assert_eq!(db.get_setting("from_thread").unwrap().as_deref(), Some("yes"));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_refuses_newer_database() {
+        let root = scratch_dir("db-newer");
+        let path = root.join("settings.db");
+        Connection::open(&path).unwrap()
+            .pragma_update(None, "user_version", MIGRATIONS.len() + 1)
+            .unwrap();
+
+        let error = Database::open(&path).err().expect("Newer schema must be rejected").to_string();
+        assert!(error.contains("Please update ProtonUp-GTK"), "{}", error);
+        assert_eq!(user_version(&path), MIGRATIONS.len() + 1);
+        std::fs::remove_dir_all(&root).unwrap();
     }
 }
@@ -0,0 +1,152 @@
+use std::path::{Path, PathBuf};
+
+use super::error::{Error, Result};
+
+/// Space an unpacked runner takes per byte of archive. GE-Proton and Wine-GE
+/// builds unpack to about 2.5x their compressed size.
+pub const EXTRACTION_FACTOR: u64 = 3;
+
+/// Free space below which a filesystem is reported as running low after an install.
+pub const LOW_SPACE_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// A filesystem that stays usable but will be nearly full after an install.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LowSpace {
+    pub path: PathBuf,
+    /// Bytes left once the install is done
+    pub remaining: u64,
+}
+
+impl std::fmt::Display for LowSpace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "only {} left in {}", format_size(self.remaining), self.path.display())
+    }
+}
+
+/// `1.5 GiB`, `300 MiB` and so on.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// The path itself or its nearest ancestor that exists, as directories are
+/// often checked before they are created.
+fn existing_ancestor(path: &Path) -> Result<&Path> {
+    path.ancestors()
+        .find(|ancestor| ancestor.exists())
+        .ok_or_else(|| Error::Other(format!("No existing directory above {}", path.display())))
+}
+
+/// Bytes an unprivileged user can still write on the filesystem holding `path`.
+#[cfg(unix)]
+pub fn available_space(path: &Path) -> Result<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let existing = existing_ancestor(path)?;
+    let c_path = std::ffi::CString::new(existing.as_os_str().as_bytes())
+        .map_err(|_| Error::Other(format!("Invalid path {}", existing.display())))?;
+    // SAFETY: statvfs only writes into the zeroed struct we pass and reads the
+    // NUL-terminated path, both valid for the duration of the call.
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(Error::io(std::io::Error::last_os_error(), existing));
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+pub fn available_space(_path: &Path) -> Result<u64> {
+    Ok(u64::MAX)
+}
+
+/// Whether both paths, or their nearest existing ancestors, are on one filesystem.
+pub fn same_filesystem(a: &Path, b: &Path) -> bool {
+    matches!((device_id(a), device_id(b)), (Ok(a), Ok(b)) if a == b)
+}
+
+/// Identifies the filesystem holding `path`, so requirements on the same one add up.
+#[cfg(unix)]
+fn device_id(path: &Path) -> Result<u64> {
+    use std::os::unix::fs::MetadataExt;
+    let existing = existing_ancestor(path)?;
+    Ok(std::fs::metadata(existing).map_err(|e| Error::io(e, existing))?.dev())
+}
+
+#[cfg(not(unix))]
+fn device_id(_path: &Path) -> Result<u64> {
+    Ok(0)
+}
+
+/// Checks that every `(directory, bytes)` requirement fits, adding up those on
+/// the same filesystem.
+///
+/// Fails with [`Error::InsufficientSpace`] for the first filesystem that is too
+/// small, and returns the filesystems that will be nearly full afterwards.
+pub fn check_space(requirements: &[(PathBuf, u64)]) -> Result<Vec<LowSpace>> {
+    let mut filesystems: Vec<(u64, &Path, u64)> = Vec::new();
+    for (path, bytes) in requirements {
+        let device = device_id(path)?;
+        match filesystems.iter_mut().find(|(id, _, _)| *id == device) {
+            Some((_, _, needed)) => *needed += bytes,
+            None => filesystems.push((device, path, *bytes)),
+        }
+    }
+
+    let mut low = Vec::new();
+    for (_, path, needed) in filesystems {
+        let available = available_space(path)?;
+        if available < needed {
+            return Err(Error::InsufficientSpace { path: path.to_path_buf(), needed, available });
+        }
+        if available - needed < LOW_SPACE_BYTES {
+            low.push(LowSpace { path: path.to_path_buf(), remaining: available - needed });
+        }
+    }
+    Ok(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::test_util::scratch_dir;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(300 * 1024 * 1024), "300.0 MiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024 / 2), "1.5 GiB");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_space() {
+        let root = scratch_dir("disk-space");
+        let available = available_space(&root).unwrap();
+        assert!(available > 0);
+        // Directories that do not exist yet are measured on their parent
+        assert!(available_space(&root.join("steam/compatibilitytools.d")).is_ok());
+
+        assert!(check_space(&[(root.clone(), 1)]).is_ok());
+
+        // Two requirements on one filesystem add up
+        let half = available / 2 + LOW_SPACE_BYTES;
+        let error = check_space(&[(root.clone(), half), (root.join("a"), half)]).unwrap_err();
+        assert!(matches!(error, Error::InsufficientSpace { .. }));
+
+        // Fitting with little to spare is reported as low
+        let low = check_space(&[(root.clone(), available.saturating_sub(LOW_SPACE_BYTES / 2))]).unwrap();
+        assert_eq!(low.len(), 1);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,17 +1,349 @@
-use anyhow::Result;
-use std::path::Path;
+use std::cell::Cell;
+use std::io::Read;
+use std::rc::Rc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 use tokio::io::AsyncWriteExt;
-use futures_util::StreamExt;
+use tokio::sync::mpsc;
 
+use super::archive_cache::{hex_digest, sha256_file, ArchiveCache};
+use super::disk_space::{check_space, format_size, EXTRACTION_FACTOR};
+use super::error::{Error, Result};
+use super::network::{rank_by_speed, HttpClient, NetworkSettings, RateLimit, RetryingBody};
+
+/// Cheap to clone; clones share the HTTP client and the archive cache.
+#[derive(Clone)]
 pub struct Downloader {
-    client: reqwest::Client,
+    http: HttpClient,
+    /// Only locked for index bookkeeping, never across an await
+    cache: Arc<Mutex<Option<ArchiveCache>>>,
+    /// Overrides the system temp directory for uncached downloads
+    temp_dir: Arc<Mutex<Option<PathBuf>>>,
+    /// Unpack uncached downloads while they arrive instead of saving them first
+    streaming: Arc<AtomicBool>,
+    /// Caps the combined speed of all downloads
+    rate_limit: RateLimit,
+    /// Try mirrors fastest first instead of in the configured order
+    probe_mirrors: Arc<AtomicBool>,
+}
+
+/// How far a download has got.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DownloadProgress {
+    pub downloaded: u64,
+    /// From `Content-Length`, which some servers omit
+    pub total: Option<u64>,
+    /// Smoothed over the last few updates
+    pub bytes_per_sec: f64,
+}
+
+impl DownloadProgress {
+    /// Share downloaded between 0 and 1, if the size is known.
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.downloaded as f64 / total as f64).min(1.0)),
+            None => None,
+        }
+    }
+
+    /// Time left at the current speed, if the size is known.
+    pub fn eta(&self) -> Option<Duration> {
+        let remaining = self.total?.saturating_sub(self.downloaded);
+        (self.bytes_per_sec > 0.0).then(|| Duration::from_secs_f64(remaining as f64 / self.bytes_per_sec))
+    }
+}
+
+/// `312.0 MiB / 480.0 MiB, 12.4 MiB/s, 14 s left`, leaving out what is unknown.
+impl std::fmt::Display for DownloadProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", format_size(self.downloaded))?;
+        if let Some(total) = self.total {
+            write!(f, " / {}", format_size(total))?;
+        }
+        if self.bytes_per_sec > 0.0 {
+            write!(f, ", {}/s", format_size(self.bytes_per_sec as u64))?;
+        }
+        match self.eta().map(|eta| eta.as_secs()) {
+            Some(secs) if secs >= 60 => write!(f, ", {} min left", secs.div_ceil(60)),
+            Some(secs) if self.downloaded < self.total.unwrap_or(0) => write!(f, ", {} s left", secs.max(1)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Tracks a download, smoothing its speed and limiting how often progress is reported.
+struct ProgressMeter {
+    progress: DownloadProgress,
+    last_update: Instant,
+    last_downloaded: u64,
+}
+
+impl ProgressMeter {
+    const INTERVAL: Duration = Duration::from_millis(250);
+    /// Weight of the newest sample in the moving average
+    const SMOOTHING: f64 = 0.3;
+
+    fn new(total: Option<u64>) -> Self {
+        Self {
+            progress: DownloadProgress { total, ..Default::default() },
+            last_update: Instant::now(),
+            last_downloaded: 0,
+        }
+    }
+
+    /// Counts `bytes` more, returning the progress when an update is due.
+    fn advance(&mut self, bytes: u64) -> Option<DownloadProgress> {
+        self.progress.downloaded += bytes;
+        let elapsed = self.last_update.elapsed();
+        if elapsed < Self::INTERVAL {
+            return None;
+        }
+        let sample = (self.progress.downloaded - self.last_downloaded) as f64 / elapsed.as_secs_f64();
+        self.progress.bytes_per_sec = if self.progress.bytes_per_sec == 0.0 {
+            sample
+        } else {
+            Self::SMOOTHING * sample + (1.0 - Self::SMOOTHING) * self.progress.bytes_per_sec
+        };
+        self.last_update = Instant::now();
+        self.last_downloaded = self.progress.downloaded;
+        Some(self.progress)
+    }
+}
+
+/// How far an extraction has got.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExtractProgress {
+    /// Bytes of the compressed archive read so far
+    pub consumed: u64,
+    /// Size of the compressed archive
+    pub total: u64,
+    /// Files, directories and links written so far
+    pub entries: u64,
+}
+
+impl ExtractProgress {
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.consumed as f64 / self.total as f64 * 100.0).min(100.0)
+    }
+}
+
+/// An archive ready for extraction.
+#[derive(Debug)]
+pub struct FetchedArchive {
+    pub path: PathBuf,
+    /// Cached archives must be left in place; others are deleted after use
+    pub cached: bool,
 }
 
 impl Downloader {
     pub fn new() -> Self {
+        Self::with_http_client(HttpClient::default())
+    }
+
+    /// Downloads with `http`, which may be shared with a [`super::ToolManager`].
+    pub fn with_http_client(http: HttpClient) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            http,
+            cache: Arc::new(Mutex::new(None)),
+            temp_dir: Arc::new(Mutex::new(None)),
+            streaming: Arc::new(AtomicBool::new(false)),
+            rate_limit: RateLimit::default(),
+            probe_mirrors: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn cache(&self) -> MutexGuard<'_, Option<ArchiveCache>> {
+        self.cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Applies `settings` to the requests started from now on, including
+    /// those of anything sharing the HTTP client.
+    pub fn configure_network(&self, settings: NetworkSettings) -> Result<()> {
+        self.http.configure(settings)
+    }
+
+    /// Enables the archive cache in its default location, or disables it.
+    pub fn configure_cache(&self, enabled: bool, max_bytes: u64) -> Result<()> {
+        let mut cache = self.cache();
+        if !enabled {
+            *cache = None;
+            return Ok(());
+        }
+        match cache.as_mut() {
+            Some(cache) => cache.set_max_bytes(max_bytes)?,
+            None => *cache = Some(ArchiveCache::open(&ArchiveCache::default_dir()?, max_bytes)?),
         }
+        Ok(())
+    }
+
+    /// Bytes used by cached archives, zero when the cache is disabled.
+    pub fn cache_size(&self) -> u64 {
+        self.cache().as_ref().map(|cache| cache.total_size()).unwrap_or(0)
+    }
+
+    /// Deletes every cached archive, including those left from when the cache was enabled.
+    pub fn clear_cache(&self) -> Result<()> {
+        match self.cache().as_mut() {
+            Some(cache) => cache.clear(),
+            None => ArchiveCache::open(&ArchiveCache::default_dir()?, 0)?.clear(),
+        }
+    }
+
+    /// Uses `dir` instead of the system temp directory, which is often a small tmpfs.
+    pub fn set_temp_dir(&self, dir: Option<PathBuf>) {
+        *self.temp_dir.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = dir;
+    }
+
+    /// Where archives are downloaded to when the cache is disabled.
+    pub fn temp_dir(&self) -> PathBuf {
+        self.temp_dir.lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+            .unwrap_or_else(std::env::temp_dir)
+    }
+
+    /// Limits downloads to `bytes_per_sec`, `None` for unlimited. Applies to
+    /// running downloads too.
+    pub fn set_rate_limit(&self, bytes_per_sec: Option<u64>) {
+        self.rate_limit.set(bytes_per_sec);
+    }
+
+    pub fn rate_limit(&self) -> Option<u64> {
+        self.rate_limit.get()
+    }
+
+    pub fn set_probe_mirrors(&self, enabled: bool) {
+        self.probe_mirrors.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn set_streaming(&self, enabled: bool) {
+        self.streaming.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether installs should use [`Self::stream_extract_to_specific_dir`].
+    /// Never while the cache is enabled, as it needs the archive on disk.
+    pub fn streams_extraction(&self) -> bool {
+        self.streaming.load(Ordering::Relaxed) && self.cache().is_none()
+    }
+
+    /// Directory downloads are written to: the cache if enabled, the temp directory otherwise.
+    pub fn download_dir(&self) -> PathBuf {
+        match self.cache().as_ref() {
+            Some(cache) => cache.dir().to_path_buf(),
+            None => self.temp_dir(),
+        }
+    }
+
+    /// Returns the archive for `url`, from the cache when possible and
+    /// downloaded otherwise, trying `mirrors` too, see [`Self::download_origins`].
+    ///
+    /// Failed downloads are retried as configured with [`Self::configure_network`].
+    /// A download that still fails or does not match `expected_sha256` is
+    /// thrown away and the next origin tried. The archive is cached under `url`
+    /// whichever origin served it.
+    pub async fn fetch_archive<F>(
+        &self,
+        url: &str,
+        mirrors: &[String],
+        expected_sha256: Option<&str>,
+        mut progress_callback: F,
+    ) -> Result<FetchedArchive>
+    where
+        F: FnMut(DownloadProgress),
+    {
+        let file_name = url.rsplit('/').next()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| Error::Other("Invalid download URL".to_string()))?;
+
+        let cache_dir = {
+            let mut cache = self.cache();
+            if let Some(path) = cache.as_mut().and_then(|cache| cache.lookup(url, expected_sha256)) {
+                return Ok(FetchedArchive { path, cached: true });
+            }
+            cache.as_ref().map(|cache| cache.dir().to_path_buf())
+        };
+
+        // Download next to the cache so inserting it is a rename
+        let download_path = match &cache_dir {
+            Some(cache_dir) => cache_dir.join(format!("{}.part", file_name)),
+            None => {
+                let temp_dir = self.temp_dir();
+                tokio::fs::create_dir_all(&temp_dir).await.map_err(|e| Error::io(e, &temp_dir))?;
+                temp_dir.join(file_name)
+            }
+        };
+
+        let origins = self.download_origins(url, mirrors).await;
+        let mut last_error = None;
+        for origin in &origins {
+            let downloaded = async {
+                match self.download_file_with_progress(origin, &download_path, &mut progress_callback).await {
+                    // Replaced upstream halfway through, so start over with the new file
+                    Err(Error::SourceChanged { .. }) => {
+                        self.download_file_with_progress(origin, &download_path, &mut progress_callback).await?
+                    }
+                    result => result?,
+                }
+                match expected_sha256 {
+                    Some(expected) => {
+                        let path = download_path.clone();
+                        let actual = tokio::task::spawn_blocking(move || sha256_file(&path)).await??;
+                        verify_checksum(file_name, expected, actual)
+                    }
+                    None => Ok(()),
+                }
+            }.await;
+            let Err(e) = downloaded else {
+                last_error = None;
+                break;
+            };
+            let _ = tokio::fs::remove_file(&download_path).await;
+            let give_up = !other_origin_may_help(&e);
+            last_error = Some(e);
+            if give_up {
+                break;
+            }
+        }
+        if let Some(e) = last_error {
+            return Err(all_origins_failed(origins.len(), e));
+        }
+
+        let Some(cache_dir) = cache_dir else {
+            return Ok(FetchedArchive { path: download_path, cached: false });
+        };
+
+        // The cache may have been disabled while downloading
+        let mut cache = self.cache();
+        let Some(cache) = cache.as_mut() else {
+            let path = cache_dir.join(file_name);
+            std::fs::rename(&download_path, &path)?;
+            return Ok(FetchedArchive { path, cached: false });
+        };
+        let path = cache.insert(url, &download_path)?;
+        Ok(FetchedArchive { path, cached: true })
+    }
+
+    /// Where to download `url` from: its `mirrors` in order, then `url` itself
+    /// as the last resort. Fastest first instead when probing is enabled with
+    /// [`Self::set_probe_mirrors`].
+    pub async fn download_origins(&self, url: &str, mirrors: &[String]) -> Vec<String> {
+        let mut origins: Vec<String> = Vec::new();
+        for origin in mirrors.iter().map(String::as_str).chain([url]) {
+            if !origins.iter().any(|known| known == origin) {
+                origins.push(origin.to_string());
+            }
+        }
+        if origins.len() > 1 && self.probe_mirrors.load(Ordering::Relaxed) {
+            let (client, settings) = self.http.current();
+            origins = rank_by_speed(&client, &settings, origins).await;
+        }
+        origins
     }
 
     pub async fn download_file_with_progress<F>(
@@ -21,60 +353,230 @@ impl Downloader {
         mut progress_callback: F,
     ) -> Result<()>
     where
-        F: FnMut(f64),
+        F: FnMut(DownloadProgress),
     {
-        let response = self.client.get(url).send().await?;
-        
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!("Failed to download file: HTTP {}", response.status()));
-        }
+        let (client, settings) = self.http.current();
+        let mut body = RetryingBody::open(&client, &settings, url).await?;
 
-        let total_size = response.content_length().unwrap_or(0);
-        let mut downloaded: u64 = 0;
+        let total_size = body.total();
+        if let (Some(dir), Some(total_size)) = (destination.parent(), total_size) {
+            check_space(&[(dir.to_path_buf(), total_size)])?;
+        }
+        let mut meter = ProgressMeter::new(total_size);
+        progress_callback(meter.progress);
 
-        let mut file = tokio::fs::File::create(destination).await?;
-        let mut stream = response.bytes_stream();
+        let mut file = tokio::fs::File::create(destination)
+            .await
+            .map_err(|e| Error::io(e, destination))?;
 
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk?;
-            file.write_all(&chunk).await?;
-            downloaded += chunk.len() as u64;
-            
-            if total_size > 0 {
-                let progress = (downloaded as f64 / total_size as f64) * 100.0;
+        while let Some(chunk) = body.chunk().await? {
+            file.write_all(&chunk).await.map_err(|e| Error::io(e, destination))?;
+            self.rate_limit.consume(chunk.len() as u64).await;
+            if let Some(progress) = meter.advance(chunk.len() as u64) {
                 progress_callback(progress);
             }
         }
 
         file.flush().await?;
-        progress_callback(100.0);
+        progress_callback(meter.progress);
         Ok(())
     }
 
-    pub async fn extract_archive_to_specific_dir(&self, archive_path: &Path, extract_to: &Path, target_dir_name: &str) -> Result<()> {
+    /// Unpacks the runner in `archive_path` to `extract_to/target_dir_name`.
+    ///
+    /// The archive is unpacked into a hidden staging directory inside
+    /// `extract_to`, so moving it into place is a rename on the same filesystem
+    /// and a failed extraction never leaves a half-written runner behind.
+    pub async fn extract_archive_to_specific_dir<F>(
+        &self,
+        archive_path: &Path,
+        extract_to: &Path,
+        target_dir_name: &str,
+        progress_callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(ExtractProgress),
+    {
+        tokio::fs::create_dir_all(extract_to).await.map_err(|e| Error::io(e, extract_to))?;
+        let staging_dir = Self::staging_dir(extract_to);
+        tokio::fs::create_dir_all(&staging_dir).await.map_err(|e| Error::io(e, &staging_dir))?;
+
+        let result = self.extract_via_staging(archive_path, &staging_dir, &extract_to.join(target_dir_name), progress_callback).await;
+        let _ = tokio::fs::remove_dir_all(&staging_dir).await;
+        result
+    }
+
+    /// A fresh hidden directory in `parent` to unpack into.
+    fn staging_dir(parent: &Path) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        parent.join(format!(".protonup-extract-{}-{}", std::process::id(), nanos))
+    }
+
+    async fn extract_via_staging<F>(&self, archive_path: &Path, staging_dir: &Path, target_path: &Path, mut progress_callback: F) -> Result<()>
+    where
+        F: FnMut(ExtractProgress),
+    {
         // Determine archive type by extension
         let extension = archive_path
             .extension()
             .and_then(|e| e.to_str())
-            .unwrap_or("");
+            .unwrap_or("")
+            .to_string();
+        if !matches!(extension.as_str(), "gz" | "tgz" | "xz") {
+            return Err(Error::UnsupportedArchive { path: archive_path.to_path_buf() });
+        }
 
-        // First extract to a temporary location
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        let temp_extract_dir = std::env::temp_dir().join(format!("protonup-extract-{}", timestamp));
-        tokio::fs::create_dir_all(&temp_extract_dir).await?;
+        // Unpack on a blocking thread and forward only the latest progress
+        let (sender, mut receiver) = tokio::sync::watch::channel(ExtractProgress::default());
+        let archive = archive_path.to_path_buf();
+        let staging = staging_dir.to_path_buf();
+        let unpack = tokio::task::spawn_blocking(move || {
+            unpack_file(&archive, &extension, &staging, |progress| {
+                let _ = sender.send(progress);
+            })
+        });
+        while receiver.changed().await.is_ok() {
+            progress_callback(*receiver.borrow_and_update());
+        }
+        unpack.await??;
+
+        Self::move_into_place(staging_dir, target_path).await
+    }
+
+    /// Downloads the runner archive at `url`, or one of its `mirrors`, and
+    /// unpacks it to `extract_to/target_dir_name` while it arrives, without
+    /// writing the archive to disk. Returns the SHA-256 of the archive.
+    ///
+    /// Nothing is moved into place when the download fails or its checksum
+    /// does not match `expected_sha256`; the next origin is tried instead.
+    pub async fn stream_extract_to_specific_dir<F>(
+        &self,
+        url: &str,
+        mirrors: &[String],
+        extract_to: &Path,
+        target_dir_name: &str,
+        expected_sha256: Option<&str>,
+        mut progress_callback: F,
+    ) -> Result<String>
+    where
+        F: FnMut(DownloadProgress),
+    {
+        tokio::fs::create_dir_all(extract_to).await.map_err(|e| Error::io(e, extract_to))?;
+
+        let origins = self.download_origins(url, mirrors).await;
+        let mut last_error = None;
+        for origin in &origins {
+            let staging_dir = Self::staging_dir(extract_to);
+            tokio::fs::create_dir_all(&staging_dir).await.map_err(|e| Error::io(e, &staging_dir))?;
 
-        // Extract the archive
-        match extension {
-            "gz" | "tgz" => self.extract_tar_gz(archive_path, &temp_extract_dir).await?,
-            "xz" => self.extract_tar_xz(archive_path, &temp_extract_dir).await?,
-            _ => return Err(anyhow::anyhow!("Unsupported archive format: {}", extension)),
+            let unpacked = async {
+                let actual = match self.stream_into(origin, &staging_dir, &mut progress_callback).await {
+                    // Replaced upstream halfway through, so start over with the new file
+                    Err(Error::SourceChanged { .. }) => {
+                        let _ = tokio::fs::remove_dir_all(&staging_dir).await;
+                        tokio::fs::create_dir_all(&staging_dir).await.map_err(|e| Error::io(e, &staging_dir))?;
+                        self.stream_into(origin, &staging_dir, &mut progress_callback).await?
+                    }
+                    result => result?,
+                };
+                if let Some(expected) = expected_sha256 {
+                    verify_checksum(target_dir_name, expected, actual.clone())?;
+                }
+                Ok(actual)
+            }.await;
+            let result = match unpacked {
+                Ok(actual) => Self::move_into_place(&staging_dir, &extract_to.join(target_dir_name))
+                    .await
+                    .map(|()| actual),
+                Err(e) => Err(e),
+            };
+            let _ = tokio::fs::remove_dir_all(&staging_dir).await;
+
+            match result {
+                Ok(actual) => return Ok(actual),
+                Err(e) => {
+                    let give_up = !other_origin_may_help(&e);
+                    last_error = Some(e);
+                    if give_up {
+                        break;
+                    }
+                }
+            }
         }
+        let error = last_error.unwrap_or_else(|| Error::Other(format!("No download source for {}", url)));
+        Err(all_origins_failed(origins.len(), error))
+    }
+
+    /// Feeds the response body to a blocking unpacker through a bounded
+    /// channel, so a slow disk slows the download down instead of buffering it.
+    async fn stream_into<F>(&self, url: &str, staging_dir: &Path, mut progress_callback: F) -> Result<String>
+    where
+        F: FnMut(DownloadProgress),
+    {
+        // Mirrors may add a query string, so only the path names the file
+        let extension = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| Path::new(url.path()).extension()?.to_str().map(str::to_string))
+            .unwrap_or_default();
+        if !matches!(extension.as_str(), "gz" | "tgz" | "xz") {
+            return Err(Error::UnsupportedArchive { path: PathBuf::from(url) });
+        }
+
+        let (client, settings) = self.http.current();
+        let mut body = RetryingBody::open(&client, &settings, url).await?;
+        // The release listing may not give a size; the response usually does
+        if let Some(total) = body.total() {
+            check_space(&[(staging_dir.to_path_buf(), total.saturating_mul(EXTRACTION_FACTOR))])?;
+        }
+        let mut meter = ProgressMeter::new(body.total());
+        progress_callback(meter.progress);
+
+        let (sender, receiver) = mpsc::channel(16);
+        let staging = staging_dir.to_path_buf();
+        let unpack = tokio::task::spawn_blocking(move || unpack_from_channel(receiver, &extension, &staging));
 
+        let mut download_error = None;
+        loop {
+            let chunk = match body.chunk().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(e) => {
+                    download_error = Some(e);
+                    break;
+                }
+            };
+            let len = chunk.len() as u64;
+            // The unpacker only hangs up on errors, which are reported below
+            if sender.send(chunk).await.is_err() {
+                break;
+            }
+            self.rate_limit.consume(len).await;
+            if let Some(progress) = meter.advance(len) {
+                progress_callback(progress);
+            }
+        }
+        drop(sender);
+
+        let unpacked = unpack.await?;
+        if let Some(e) = download_error {
+            return Err(e);
+        }
+        let sha256 = unpacked?;
+        if meter.progress.downloaded < meter.progress.total.unwrap_or(0) {
+            return Err(Error::Other(format!("Download of {} ended early", url)));
+        }
+        progress_callback(meter.progress);
+        Ok(sha256)
+    }
+
+    /// Moves the directory unpacked into `staging_dir` to `target_path`,
+    /// replacing what is there.
+    async fn move_into_place(staging_dir: &Path, target_path: &Path) -> Result<()> {
         // Find the extracted content (usually a single directory)
-        let mut entries = tokio::fs::read_dir(&temp_extract_dir).await?;
+        let mut entries = tokio::fs::read_dir(staging_dir).await?;
         let mut extracted_dir = None;
         
         while let Some(entry) = entries.next_entry().await? {
@@ -84,32 +586,60 @@ impl Downloader {
             }
         }
 
-        let source_dir = extracted_dir.ok_or_else(|| anyhow::anyhow!("No directory found in extracted archive"))?;
+        let source_dir = extracted_dir.ok_or_else(|| Error::Other("No directory found in extracted archive".to_string()))?;
+        
+        // Keep what is there until the new tree has taken its place
+        let previous = Self::set_aside_path(target_path);
+        let replacing = target_path.exists();
+        if replacing {
+            let _ = tokio::fs::remove_dir_all(&previous).await;
+            tokio::fs::rename(target_path, &previous).await.map_err(|e| Error::io(e, target_path))?;
+        }
+        
+        if let Err(e) = tokio::fs::rename(&source_dir, target_path).await {
+            if replacing {
+                let _ = tokio::fs::rename(&previous, target_path).await;
+            }
+            return Err(Error::io(e, target_path));
+        }
+        if replacing {
+            let _ = tokio::fs::remove_dir_all(&previous).await;
+        }
+        Ok(())
+    }
+
+    /// Hidden sibling `target_path` is moved to while its replacement is put in place.
+    pub fn set_aside_path(target_path: &Path) -> PathBuf {
+        let name = target_path.file_name().unwrap_or_default().to_string_lossy();
+        target_path.with_file_name(format!(".protonup-previous-{}", name))
+    }
+
+    /// Copies an already-extracted runner directory into place, like
+    /// [`Self::extract_archive_to_specific_dir`] does for archives.
+    pub async fn copy_dir_to_specific_dir(&self, source_dir: &Path, extract_to: &Path, target_dir_name: &str) -> Result<()> {
+        if !source_dir.is_dir() {
+            return Err(Error::Other(format!("{} is not a directory", source_dir.display())));
+        }
         
-        // Create the target directory with the specified name
         tokio::fs::create_dir_all(extract_to).await?;
-        let target_path = extract_to.join(target_dir_name);
+        // Compare resolved paths so symlinks and `..` cannot hide an overlap
+        let source_dir = tokio::fs::canonicalize(source_dir).await?;
+        let target_path = tokio::fs::canonicalize(extract_to).await?.join(target_dir_name);
+        
+        if target_path.starts_with(&source_dir) || source_dir.starts_with(&target_path) {
+            return Err(Error::Other(format!(
+                "Cannot copy {} into {}: one directory contains the other",
+                source_dir.display(),
+                target_path.display()
+            )));
+        }
         
         // If target already exists, remove it first
         if target_path.exists() {
             tokio::fs::remove_dir_all(&target_path).await?;
         }
         
-        // Try to rename first (fast if same filesystem), fall back to copy if it fails
-        match tokio::fs::rename(&source_dir, &target_path).await {
-            Ok(_) => {},
-            Err(_) => {
-                // Rename failed (likely different filesystem), so copy instead
-                Self::copy_dir_recursive(&source_dir, &target_path).await?;
-                // Remove the source after successful copy
-                let _ = tokio::fs::remove_dir_all(&source_dir).await;
-            }
-        }
-        
-        // Clean up the temporary extraction directory
-        let _ = tokio::fs::remove_dir_all(&temp_extract_dir).await;
-        
-        Ok(())
+        Self::copy_dir_recursive(&source_dir, &target_path).await
     }
 
     async fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
@@ -151,27 +681,221 @@ impl Downloader {
         Ok(())
     }
 
-    async fn extract_tar_gz(&self, archive_path: &Path, extract_to: &Path) -> Result<()> {
-        let file = std::fs::File::open(archive_path)?;
-        let decoder = flate2::read::GzDecoder::new(file);
-        let mut archive = tar::Archive::new(decoder);
+    /// Replicates an installed runner into another launcher's directory.
+    ///
+    /// Each file is reflinked where the filesystem supports it, hardlinked when
+    /// source and target share a filesystem, and copied otherwise, so fanning one
+    /// extraction out to several launchers costs little extra space or time.
+    pub async fn replicate_dir(src: &Path, dst: &Path) -> Result<()> {
+        if dst.exists() {
+            tokio::fs::remove_dir_all(dst).await?;
+        }
+        if let Some(parent) = dst.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
         
-        tokio::fs::create_dir_all(extract_to).await?;
-        archive.unpack(extract_to)?;
+        let src = src.to_path_buf();
+        let dst = dst.to_path_buf();
+        tokio::task::spawn_blocking(move || Self::replicate_dir_blocking(&src, &dst)).await?
+    }
+
+    fn replicate_dir_blocking(src: &Path, dst: &Path) -> Result<()> {
+        std::fs::create_dir_all(dst)?;
+        std::fs::set_permissions(dst, std::fs::metadata(src)?.permissions())?;
+        
+        for entry in std::fs::read_dir(src)? {
+            let entry = entry?;
+            let entry_path = entry.path();
+            let dest_path = dst.join(entry.file_name());
+            let metadata = std::fs::symlink_metadata(&entry_path)?;
+            
+            if metadata.is_symlink() {
+                #[cfg(unix)]
+                std::os::unix::fs::symlink(std::fs::read_link(&entry_path)?, &dest_path)?;
+            } else if metadata.is_dir() {
+                Self::replicate_dir_blocking(&entry_path, &dest_path)?;
+            } else if metadata.is_file() {
+                Self::replicate_file(&entry_path, &dest_path, &metadata)?;
+            }
+            // Skip other file types (devices, sockets, etc.)
+        }
         
         Ok(())
     }
 
-    async fn extract_tar_xz(&self, archive_path: &Path, extract_to: &Path) -> Result<()> {
-        let file = std::fs::File::open(archive_path)?;
-        let decoder = xz2::read::XzDecoder::new(file);
-        let mut archive = tar::Archive::new(decoder);
-        
-        tokio::fs::create_dir_all(extract_to).await?;
-        archive.unpack(extract_to)?;
+    fn replicate_file(src: &Path, dst: &Path, metadata: &std::fs::Metadata) -> Result<()> {
+        if Self::reflink_file(src, dst, metadata).is_ok() {
+            return Ok(());
+        }
+        if std::fs::hard_link(src, dst).is_ok() {
+            return Ok(());
+        }
+        std::fs::copy(src, dst)?;
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn reflink_file(src: &Path, dst: &Path, metadata: &std::fs::Metadata) -> Result<()> {
+        use std::os::unix::io::AsRawFd;
         
+        let source = std::fs::File::open(src)?;
+        let target = std::fs::File::create(dst)?;
+        // SAFETY: both descriptors are valid for the duration of the call and
+        // FICLONE only reads the source descriptor number passed as argument.
+        let result = unsafe { libc::ioctl(target.as_raw_fd(), libc::FICLONE, source.as_raw_fd()) };
+        if result != 0 {
+            let error = std::io::Error::last_os_error();
+            drop(target);
+            let _ = std::fs::remove_file(dst);
+            return Err(error.into());
+        }
+        target.set_permissions(metadata.permissions())?;
         Ok(())
     }
+
+    #[cfg(not(target_os = "linux"))]
+    fn reflink_file(_src: &Path, _dst: &Path, _metadata: &std::fs::Metadata) -> Result<()> {
+        Err(Error::Other("Reflinks are not supported on this platform".to_string()))
+    }
+
+}
+
+/// Fails with [`Error::ChecksumMismatch`] unless `actual` is the `expected` SHA-256.
+fn verify_checksum(name: &str, expected: &str, actual: String) -> Result<()> {
+    if actual.eq_ignore_ascii_case(expected) {
+        return Ok(());
+    }
+    Err(Error::ChecksumMismatch { name: name.to_string(), expected: expected.to_string(), actual })
+}
+
+/// Whether downloading from another origin could get past `error`. Running out
+/// of disk space or permissions does not depend on the origin.
+fn other_origin_may_help(error: &Error) -> bool {
+    !matches!(
+        error,
+        Error::DiskFull { .. } | Error::InsufficientSpace { .. } | Error::PermissionDenied { .. }
+    )
+}
+
+/// The error to report once `error` was the last of `origins` attempts.
+fn all_origins_failed(origins: usize, error: Error) -> Error {
+    if origins > 1 && other_origin_may_help(&error) {
+        Error::MirrorsFailed { origins, source: Box::new(error) }
+    } else {
+        error
+    }
+}
+
+/// Counts the bytes read through it.
+struct CountingReader<R> {
+    inner: R,
+    count: Rc<Cell<u64>>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count.set(self.count.get() + read as u64);
+        Ok(read)
+    }
+}
+
+/// Unpacks the archive at `archive_path` into `dest`, reporting progress after
+/// every entry. Must run on a blocking thread.
+fn unpack_file<F>(archive_path: &Path, extension: &str, dest: &Path, mut progress_callback: F) -> Result<()>
+where
+    F: FnMut(ExtractProgress),
+{
+    let file = std::fs::File::open(archive_path).map_err(|e| Error::io(e, archive_path))?;
+    let total = file.metadata().map_err(|e| Error::io(e, archive_path))?.len();
+    let consumed = Rc::new(Cell::new(0));
+    let reader = CountingReader { inner: file, count: consumed.clone() };
+    let decoder: Box<dyn Read> = match extension {
+        "gz" | "tgz" => Box::new(flate2::read::GzDecoder::new(reader)),
+        "xz" => Box::new(xz2::read::XzDecoder::new(reader)),
+        _ => return Err(Error::UnsupportedArchive { path: archive_path.to_path_buf() }),
+    };
+    unpack_tar(decoder, dest, |entries| {
+        progress_callback(ExtractProgress { consumed: consumed.get(), total, entries });
+    })
+}
+
+/// Like [`tar::Archive::unpack`], calling `on_entry` with the number of
+/// entries written so far after each one.
+fn unpack_tar<R: Read>(reader: R, dest: &Path, mut on_entry: impl FnMut(u64)) -> Result<()> {
+    let dest = dest.canonicalize().map_err(|e| Error::io(e, dest))?;
+    let mut archive = tar::Archive::new(reader);
+    let mut directories = Vec::new();
+    let mut entries = 0;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        // Directories are finished last, so read-only ones do not block their contents
+        if entry.header().entry_type() == tar::EntryType::Directory {
+            directories.push(entry);
+        } else {
+            entry.unpack_in(&dest).map_err(|e| Error::io(e, &dest))?;
+        }
+        entries += 1;
+        on_entry(entries);
+    }
+    directories.sort_by(|a, b| b.path_bytes().cmp(&a.path_bytes()));
+    for mut directory in directories {
+        directory.unpack_in(&dest).map_err(|e| Error::io(e, &dest))?;
+    }
+    Ok(())
+}
+
+/// Reads the chunks sent by the download task, hashing everything read.
+struct ChannelReader<T> {
+    receiver: mpsc::Receiver<T>,
+    chunk: Option<T>,
+    offset: usize,
+    digest: ring::digest::Context,
+}
+
+impl<T: AsRef<[u8]>> Read for ChannelReader<T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            if let Some(chunk) = &self.chunk {
+                let rest = &chunk.as_ref()[self.offset..];
+                if !rest.is_empty() {
+                    let len = rest.len().min(buf.len());
+                    buf[..len].copy_from_slice(&rest[..len]);
+                    self.digest.update(&rest[..len]);
+                    self.offset += len;
+                    return Ok(len);
+                }
+            }
+            match self.receiver.blocking_recv() {
+                Some(chunk) => {
+                    self.chunk = Some(chunk);
+                    self.offset = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+    }
+}
+
+/// Unpacks the tarball arriving on `receiver` into `staging_dir` and returns
+/// the SHA-256 of the compressed stream. Must run on a blocking thread.
+fn unpack_from_channel<T: AsRef<[u8]>>(receiver: mpsc::Receiver<T>, extension: &str, staging_dir: &Path) -> Result<String> {
+    let mut reader = ChannelReader {
+        receiver,
+        chunk: None,
+        offset: 0,
+        digest: ring::digest::Context::new(&ring::digest::SHA256),
+    };
+    let decoder: Box<dyn Read + '_> = match extension {
+        "gz" | "tgz" => Box::new(flate2::read::GzDecoder::new(&mut reader)),
+        "xz" => Box::new(xz2::read::XzDecoder::new(&mut reader)),
+        _ => return Err(Error::UnsupportedArchive { path: staging_dir.to_path_buf() }),
+    };
+    unpack_tar(decoder, staging_dir, |_| {})?;
+
+    // Hash whatever follows the end of the tarball too
+    std::io::copy(&mut reader, &mut std::io::sink())?;
+    Ok(hex_digest(reader.digest))
 }
 
 impl Default for Downloader {
@@ -179,3 +903,258 @@ impl Default for Downloader {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::test_util::scratch_dir;
+
+    #[tokio::test]
+    async fn test_replicate_dir() {
+        let root = scratch_dir("replicate");
+        let src = root.join("src/lutris-GE-Proton8-26-x86_64");
+        std::fs::create_dir_all(src.join("bin")).unwrap();
+        std::fs::create_dir_all(src.join("lib/wine")).unwrap();
+        std::fs::write(src.join("bin/wine"), b"#!/bin/sh\n").unwrap();
+        std::fs::write(src.join("lib/wine/ntdll.so"), b"ntdll").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(src.join("bin/wine"), std::fs::Permissions::from_mode(0o755)).unwrap();
+            std::os::unix::fs::symlink("wine", src.join("bin/wine64")).unwrap();
+        }
+
+        let dst = root.join("dst/lutris-GE-Proton8-26-x86_64");
+        std::fs::create_dir_all(&dst).unwrap();
+        std::fs::write(dst.join("stale"), b"old").unwrap();
+
+        Downloader::replicate_dir(&src, &dst).await.unwrap();
+
+        assert_eq!(std::fs::read(dst.join("lib/wine/ntdll.so")).unwrap(), b"ntdll");
+        assert!(!dst.join("stale").exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(dst.join("bin/wine")).unwrap().permissions().mode();
+            assert_ne!(mode & 0o111, 0);
+            assert_eq!(std::fs::read_link(dst.join("bin/wine64")).unwrap(), Path::new("wine"));
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_copy_dir_rejects_overlap() {
+        let root = scratch_dir("copy-overlap");
+        let source = root.join("compatibilitytools.d/GE-Proton9-20");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("proton"), b"proton").unwrap();
+        let downloader = Downloader::new();
+
+        // The same directory, reached through `..`
+        let same_parent = source.join("../../compatibilitytools.d");
+        assert!(downloader.copy_dir_to_specific_dir(&source, &same_parent, "GE-Proton9-20").await.is_err());
+        // Target inside the source, or the source inside the target
+        assert!(downloader.copy_dir_to_specific_dir(&source, &source, "nested").await.is_err());
+        assert!(downloader.copy_dir_to_specific_dir(&source, &root, "compatibilitytools.d").await.is_err());
+        assert_eq!(std::fs::read(source.join("proton")).unwrap(), b"proton");
+
+        downloader.copy_dir_to_specific_dir(&source, &root.join("lutris"), "GE-Proton9-20").await.unwrap();
+        assert_eq!(std::fs::read(root.join("lutris/GE-Proton9-20/proton")).unwrap(), b"proton");
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_download_progress() {
+        const MIB: u64 = 1024 * 1024;
+        let progress = DownloadProgress { downloaded: 312 * MIB, total: Some(480 * MIB), bytes_per_sec: 12.0 * MIB as f64 };
+        assert_eq!(progress.eta(), Some(Duration::from_secs(14)));
+        assert_eq!(progress.to_string(), "312.0 MiB / 480.0 MiB, 12.0 MiB/s, 14 s left");
+
+        // Without Content-Length there is no fraction or ETA
+        let unknown = DownloadProgress { total: None, ..progress };
+        assert_eq!(unknown.fraction(), None);
+        assert_eq!(unknown.to_string(), "312.0 MiB, 12.0 MiB/s");
+
+        let slow = DownloadProgress { bytes_per_sec: MIB as f64, ..progress };
+        assert_eq!(slow.to_string(), "312.0 MiB / 480.0 MiB, 1.0 MiB/s, 3 min left");
+
+        let mut meter = ProgressMeter::new(Some(10 * MIB));
+        assert_eq!(meter.advance(MIB), None, "Updates are throttled");
+        meter.last_update -= ProgressMeter::INTERVAL;
+        let update = meter.advance(MIB).unwrap();
+        assert_eq!(update.downloaded, 2 * MIB);
+        assert!(update.bytes_per_sec > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_extract_progress() {
+        let root = scratch_dir("extract-progress");
+        let build = root.join("build/wine-ge-8-26");
+        std::fs::create_dir_all(build.join("bin")).unwrap();
+        std::fs::write(build.join("bin/wine"), b"#!/bin/sh\n").unwrap();
+        std::fs::write(build.join("bin/wineserver"), b"#!/bin/sh\n").unwrap();
+        let archive = root.join("wine-ge-8-26.tar.xz");
+        let encoder = xz2::write::XzEncoder::new(std::fs::File::create(&archive).unwrap(), 1);
+        let mut builder = tar::Builder::new(encoder);
+        builder.append_dir_all("wine-ge-8-26", &build).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let mut updates = Vec::new();
+        let install_path = root.join("runners");
+        Downloader::new()
+            .extract_archive_to_specific_dir(&archive, &install_path, "wine-ge-8-26", |progress| updates.push(progress))
+            .await
+            .unwrap();
+
+        assert!(install_path.join("wine-ge-8-26/bin/wineserver").is_file());
+        let last = updates.last().expect("progress was reported");
+        assert_eq!(last.entries, 4);
+        assert_eq!(last.total, std::fs::metadata(&archive).unwrap().len());
+        assert!(last.percent() > 0.0);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mirror_fallback() {
+        use crate::backend::test_util::{http_response, serve, sha256_hex, tar_gz, write_steam_tool};
+
+        let root = scratch_dir("mirrors");
+        let build = root.join("build/GE-Proton9-20");
+        write_steam_tool(&build);
+        let archive: &'static [u8] = tar_gz(&build).leak();
+        let sha256 = sha256_hex(archive);
+
+        let release = serve(|_, _| http_response("200 OK", &[], archive, archive.len())).await;
+        let missing = serve(|_, _| http_response("404 Not Found", &[], b"", 0)).await;
+        let tampered = serve(|_, _| http_response("200 OK", &[], b"tampered", 8)).await;
+        let url = format!("{}/GE-Proton9-20.tar.gz", release);
+        let mirrors = vec![format!("{}/GE-Proton9-20.tar.gz", missing), format!("{}/GE-Proton9-20.tar.gz", tampered)];
+
+        let downloader = Downloader::new();
+        downloader.set_temp_dir(Some(root.join("tmp")));
+        downloader.configure_network(NetworkSettings { max_attempts: 1, ..Default::default() }).unwrap();
+        assert_eq!(downloader.download_origins(&url, &mirrors).await, vec![mirrors[0].clone(), mirrors[1].clone(), url.clone()]);
+
+        // Mirrors that fail or serve other bytes are skipped
+        let fetched = downloader.fetch_archive(&url, &mirrors, Some(&sha256), |_| {}).await.unwrap();
+        assert_eq!(std::fs::read(&fetched.path).unwrap(), archive);
+
+        let error = downloader.fetch_archive(&mirrors[1], &mirrors[..1], Some(&sha256), |_| {}).await.unwrap_err();
+        match error {
+            Error::MirrorsFailed { origins: 2, source } => assert!(matches!(*source, Error::ChecksumMismatch { .. })),
+            other => panic!("unexpected error: {}", other),
+        }
+
+        let install_path = root.join("compatibilitytools.d");
+        let actual = downloader
+            .stream_extract_to_specific_dir(&url, &mirrors, &install_path, "GE-Proton9-20", Some(&sha256), |_| {})
+            .await
+            .unwrap();
+        assert_eq!(actual, sha256);
+        assert!(install_path.join("GE-Proton9-20/proton").is_file());
+
+        // The archive type comes from the URL path, not its query
+        let signed = format!("{}/GE-Proton9-20.tar.gz?expires=1700000000&sig=a.b", release);
+        downloader
+            .stream_extract_to_specific_dir(&signed, &[], &install_path, "GE-Proton9-20", Some(&sha256), |_| {})
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_restart_when_source_changed() {
+        use crate::backend::test_util::{http_response, serve, tar_gz, write_steam_tool};
+
+        let root = scratch_dir("source-changed");
+        let build = root.join("build/GE-Proton9-20");
+        write_steam_tool(&build);
+        let old: &'static [u8] = tar_gz(&build).leak();
+        std::fs::write(build.join("version"), "rebuilt").unwrap();
+        let new: &'static [u8] = tar_gz(&build).leak();
+
+        // Each download is cut short once, after which the release is replaced
+        let url = serve(move |index, _| {
+            if index % 3 == 0 {
+                http_response("200 OK", &[("ETag", "\"first\"".to_string())], &old[..old.len() / 2], old.len())
+            } else {
+                http_response("200 OK", &[("ETag", "\"rebuilt\"".to_string())], new, new.len())
+            }
+        }).await;
+        let url = format!("{}/GE-Proton9-20.tar.gz", url);
+
+        let downloader = Downloader::new();
+        downloader.set_temp_dir(Some(root.join("tmp")));
+        downloader.configure_network(NetworkSettings { max_attempts: 2, initial_backoff: Duration::from_millis(1), ..Default::default() }).unwrap();
+        let fetched = downloader.fetch_archive(&url, &[], None, |_| {}).await.unwrap();
+        assert_eq!(std::fs::read(&fetched.path).unwrap(), new);
+
+        let install_path = root.join("compatibilitytools.d");
+        downloader
+            .stream_extract_to_specific_dir(&url, &[], &install_path, "GE-Proton9-20", None, |_| {})
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(install_path.join("GE-Proton9-20/version")).unwrap(), "rebuilt");
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_streaming_checks_content_length() {
+        use crate::backend::test_util::{http_response, serve};
+
+        // Claims far more than any test machine has free, and sends nothing
+        let huge = serve(|_, _| http_response("200 OK", &[], b"", 1 << 50)).await;
+        let root = scratch_dir("stream-space");
+        let downloader = Downloader::new();
+        downloader.configure_network(NetworkSettings { max_attempts: 1, ..Default::default() }).unwrap();
+
+        let install_path = root.join("compatibilitytools.d");
+        let error = downloader
+            .stream_extract_to_specific_dir(&format!("{}/GE-Proton9-20.tar.gz", huge), &[], &install_path, "GE-Proton9-20", None, |_| {})
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::InsufficientSpace { .. }), "{}", error);
+        assert_eq!(std::fs::read_dir(&install_path).unwrap().count(), 0, "The staging directory is removed");
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unpack_from_channel() {
+        let root = scratch_dir("unpack-stream");
+        let build = root.join("GE-Proton9-20");
+        std::fs::create_dir_all(build.join("files/bin")).unwrap();
+        std::fs::write(build.join("files/bin/wine"), vec![7u8; 100_000]).unwrap();
+        let archive = root.join("GE-Proton9-20.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(std::fs::File::create(&archive).unwrap(), flate2::Compression::fast());
+        let mut builder = tar::Builder::new(encoder);
+        builder.append_dir_all("GE-Proton9-20", &build).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        // Chunks of odd sizes, as they come off the network
+        let bytes = std::fs::read(&archive).unwrap();
+        let (sender, receiver) = mpsc::channel(4);
+        let staging = root.join("staging");
+        std::fs::create_dir_all(&staging).unwrap();
+        let staging_clone = staging.clone();
+        let unpack = tokio::task::spawn_blocking(move || unpack_from_channel(receiver, "gz", &staging_clone));
+        for chunk in bytes.chunks(1000) {
+            sender.send(chunk.to_vec()).await.unwrap();
+        }
+        drop(sender);
+
+        let sha256 = unpack.await.unwrap().unwrap();
+        assert_eq!(sha256, crate::backend::archive_cache::sha256_file(&archive).unwrap());
+        assert_eq!(std::fs::read(staging.join("GE-Proton9-20/files/bin/wine")).unwrap().len(), 100_000);
+
+        // A truncated stream fails instead of leaving a partial runner looking complete
+        let (sender, receiver) = mpsc::channel(4);
+        let truncated = root.join("truncated");
+        std::fs::create_dir_all(&truncated).unwrap();
+        let truncated_clone = truncated.clone();
+        let unpack = tokio::task::spawn_blocking(move || unpack_from_channel(receiver, "gz", &truncated_clone));
+        sender.send(bytes[..bytes.len() / 2].to_vec()).await.unwrap();
+        drop(sender);
+        assert!(unpack.await.unwrap().is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,223 @@
+use std::path::{Path, PathBuf};
+
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+
+use super::disk_space::format_size;
+use super::Launcher;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Everything the backend can fail with. The variants the user can act on are
+/// kept apart so the UI can explain them and offer a retry where it helps.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The request never got a response, e.g. no connection or a timeout
+    #[error("Network error: {0}")]
+    Network(#[from] reqwest::Error),
+    #[error("{url} returned HTTP {status}")]
+    HttpStatus { url: String, status: u16 },
+    #[error("The connection to {url} closed before the download finished")]
+    Interrupted { url: String },
+    /// The file was replaced upstream between two parts of a resumed download
+    #[error("{url} changed while it was downloading")]
+    SourceChanged { url: String },
+    /// Every attempt at a download failed, `source` is why the last one did
+    #[error("Download failed after {attempts} attempts: {source}")]
+    RetriesExhausted { attempts: u32, source: Box<Error> },
+    /// Neither the release URL nor any of its mirrors served the archive
+    #[error("All {origins} download sources failed, the last with: {source}")]
+    MirrorsFailed { origins: usize, source: Box<Error> },
+    /// The release API refused the request until the rate limit resets
+    #[error("API rate limit exceeded{}", retry_after_suffix(*.retry_after))]
+    RateLimited { retry_after: Option<u64> },
+    #[error("Checksum mismatch for {name}: expected {expected}, got {actual}")]
+    ChecksumMismatch { name: String, expected: String, actual: String },
+    #[error("Not enough disk space{}", in_path(.path))]
+    DiskFull { path: Option<PathBuf> },
+    /// Found before starting, so nothing was written
+    #[error("Not enough disk space in {}: {} needed, {} available", .path.display(), format_size(*.needed), format_size(*.available))]
+    InsufficientSpace { path: PathBuf, needed: u64, available: u64 },
+    #[error("Permission denied{}", in_path(.path))]
+    PermissionDenied { path: Option<PathBuf> },
+    #[error("Unsupported archive format: {}", .path.display())]
+    UnsupportedArchive { path: PathBuf },
+    /// A process of the launcher runs from the directory being changed
+    #[error("{launcher} is still using this runner")]
+    LauncherRunning { launcher: Launcher },
+    #[error(transparent)]
+    Io(std::io::Error),
+    #[error("Settings database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("{0}")]
+    Other(String),
+}
+
+fn in_path(path: &Option<PathBuf>) -> String {
+    path.as_ref().map(|path| format!(" in {}", path.display())).unwrap_or_default()
+}
+
+fn retry_after_suffix(retry_after: Option<u64>) -> String {
+    match retry_after {
+        Some(secs) if secs >= 60 => format!(", try again in {} minutes", secs.div_ceil(60)),
+        Some(secs) => format!(", try again in {} seconds", secs),
+        None => String::new(),
+    }
+}
+
+impl Error {
+    /// Classifies an I/O error, naming `path` for the disk space and permission cases.
+    pub fn io(err: std::io::Error, path: &Path) -> Self {
+        Self::from(err).at(path)
+    }
+
+    /// Names `path` in disk space and permission errors that do not name one yet.
+    pub fn at(self, path: &Path) -> Self {
+        match self {
+            Error::DiskFull { path: None } => Error::DiskFull { path: Some(path.to_path_buf()) },
+            Error::PermissionDenied { path: None } => Error::PermissionDenied { path: Some(path.to_path_buf()) },
+            other => other,
+        }
+    }
+
+    /// Whether trying the same thing again later can succeed without the user changing anything.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Network(_) | Error::RateLimited { .. } | Error::Interrupted { .. } | Error::SourceChanged { .. } => true,
+            Error::HttpStatus { status, .. } => *status == 408 || *status >= 500,
+            Error::RetriesExhausted { source, .. } | Error::MirrorsFailed { source, .. } => source.is_retryable(),
+            _ => false,
+        }
+    }
+
+    /// What the user can do about the error, if anything.
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            Error::Network(_) | Error::Interrupted { .. } => Some("Check your internet connection and try again".to_string()),
+            Error::RetriesExhausted { source, .. } => source.hint(),
+            Error::MirrorsFailed { .. } => Some("Check the mirrors set up in Preferences".to_string()),
+            Error::RateLimited { .. } => Some("Too many requests were made to the release API, wait a while before refreshing".to_string()),
+            Error::ChecksumMismatch { .. } => Some("The archive may be damaged or was replaced upstream".to_string()),
+            Error::SourceChanged { .. } => Some("The archive was replaced upstream, try again to get the new one".to_string()),
+            Error::DiskFull { .. } | Error::InsufficientSpace { .. } => Some("Free up some disk space and try again".to_string()),
+            Error::PermissionDenied { .. } => Some("Choose a writable install directory in Preferences".to_string()),
+            Error::UnsupportedArchive { .. } => Some("Only .tar.gz and .tar.xz archives can be installed".to_string()),
+            Error::LauncherRunning { launcher } => Some(format!("Close {} and any running games, then try again", launcher)),
+            _ => None,
+        }
+    }
+
+    /// The error followed by its hint, for toasts and dialogs.
+    pub fn user_message(&self) -> String {
+        match self.hint() {
+            Some(hint) => format!("{}. {}", self, hint),
+            None => self.to_string(),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        use std::io::ErrorKind;
+        match err.kind() {
+            ErrorKind::StorageFull | ErrorKind::QuotaExceeded => Error::DiskFull { path: None },
+            ErrorKind::PermissionDenied | ErrorKind::ReadOnlyFilesystem => Error::PermissionDenied { path: None },
+            _ => Error::Io(err),
+        }
+    }
+}
+
+impl From<tokio::task::JoinError> for Error {
+    fn from(err: tokio::task::JoinError) -> Self {
+        Error::Other(format!("Background task failed: {}", err))
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Self {
+        Error::Other(format!("Invalid TOML: {}", err))
+    }
+}
+
+impl From<toml::ser::Error> for Error {
+    fn from(err: toml::ser::Error) -> Self {
+        Error::Other(format!("Could not write TOML: {}", err))
+    }
+}
+
+/// Passes successful responses through and turns the others into
+/// [`Error::RateLimited`] or [`Error::HttpStatus`].
+pub fn check_response(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    Err(status_error(response.url().as_str(), status, response.headers()))
+}
+
+fn status_error(url: &str, status: StatusCode, headers: &HeaderMap) -> Error {
+    let header = |name: &str| {
+        headers.get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+
+    // GitHub answers 403 once the hourly quota is used up, others use 429
+    let exhausted = header("x-ratelimit-remaining") == Some(0);
+    if status == StatusCode::TOO_MANY_REQUESTS || (status == StatusCode::FORBIDDEN && exhausted) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let retry_after = header("retry-after")
+            .or_else(|| header("x-ratelimit-reset").map(|reset| reset.saturating_sub(now)));
+        return Error::RateLimited { retry_after };
+    }
+
+    Error::HttpStatus { url: url.to_string(), status: status.as_u16() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_status_error() {
+        let url = "https://api.github.com/repos/x/y/releases";
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+        let error = status_error(url, StatusCode::FORBIDDEN, &headers);
+        assert!(matches!(error, Error::RateLimited { .. }));
+        assert!(error.is_retryable());
+
+        headers.insert("retry-after", HeaderValue::from_static("120"));
+        let error = status_error(url, StatusCode::TOO_MANY_REQUESTS, &headers);
+        assert!(matches!(error, Error::RateLimited { retry_after: Some(120) }));
+        assert_eq!(error.to_string(), "API rate limit exceeded, try again in 2 minutes");
+
+        // A plain 403 is not a rate limit and retrying will not help
+        let error = status_error(url, StatusCode::FORBIDDEN, &HeaderMap::new());
+        assert!(matches!(error, Error::HttpStatus { status: 403, .. }));
+        assert!(!error.is_retryable());
+        assert!(status_error(url, StatusCode::BAD_GATEWAY, &HeaderMap::new()).is_retryable());
+    }
+
+    #[test]
+    fn test_io_classification() {
+        let full = std::io::Error::from(std::io::ErrorKind::StorageFull);
+        let error = Error::io(full, Path::new("/mnt/games"));
+        assert!(matches!(error, Error::DiskFull { .. }));
+        assert_eq!(error.to_string(), "Not enough disk space in /mnt/games");
+        assert!(error.user_message().ends_with("Free up some disk space and try again"));
+
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert!(matches!(Error::from(denied), Error::PermissionDenied { path: None }));
+
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert!(matches!(Error::from(missing), Error::Io(_)));
+    }
+}
@@ -0,0 +1,557 @@
+use std::path::{Component, Path, PathBuf};
+
+use super::archive_cache::sha256_file;
+use super::database::{Database, InstalledRunner, RunnerSource};
+use super::disk_space::{check_space, format_size, same_filesystem, EXTRACTION_FACTOR};
+use super::downloader::Downloader;
+use super::validation::validate_install;
+use super::{Launcher, ToolManager};
+use super::error::{Error, Result};
+
+/// Where the files of a runner come from.
+#[derive(Debug, Clone, Copy)]
+pub enum InstallSource<'a> {
+    /// A `.tar.gz`/`.tar.xz` archive, downloaded or picked from disk
+    Archive(&'a Path),
+    /// A directory holding an already-extracted runner
+    Directory(&'a Path),
+    /// An archive unpacked while it downloads, see [`Downloader::stream_extract_to_specific_dir`]
+    Download {
+        url: &'a str,
+        /// Tried before `url`, see [`Downloader::download_origins`]
+        mirrors: &'a [String],
+        sha256: Option<&'a str>,
+        /// Archive size if known, for the disk space check
+        size: Option<u64>,
+    },
+}
+
+impl<'a> InstallSource<'a> {
+    pub fn from_path(path: &'a Path) -> Self {
+        if path.is_dir() {
+            InstallSource::Directory(path)
+        } else {
+            InstallSource::Archive(path)
+        }
+    }
+
+    /// Version name derived from the file or directory name, e.g.
+    /// `GE-Proton9-20` for `GE-Proton9-20.tar.gz`.
+    pub fn default_version_name(&self) -> Option<String> {
+        let name = match self {
+            InstallSource::Archive(path) | InstallSource::Directory(path) => path.file_name()?.to_str()?,
+            InstallSource::Download { url, .. } => url.rsplit('/').next()?,
+        };
+        let name = match self {
+            InstallSource::Archive(_) | InstallSource::Download { .. } => [".tar.gz", ".tar.xz", ".tgz"]
+                .iter()
+                .find_map(|ext| name.strip_suffix(ext))
+                .unwrap_or(name),
+            InstallSource::Directory(_) => name,
+        };
+        Some(name.to_string()).filter(|n| !n.is_empty())
+    }
+}
+
+/// Outcome of installing one version into several launchers.
+#[derive(Debug, Default)]
+pub struct InstallReport {
+    pub installed: Vec<Launcher>,
+    /// One human-readable reason per launcher that could not be installed
+    pub failures: Vec<String>,
+    /// Filesystems that are nearly full after the install
+    pub warnings: Vec<String>,
+}
+
+impl InstallReport {
+    /// Success message naming the launchers, or an error if nothing was installed.
+    pub fn into_message(self, tool_name: &str, version: &str) -> Result<String> {
+        if self.installed.is_empty() {
+            return Err(Error::Other(self.failures.join("; ")));
+        }
+
+        let launchers: Vec<String> = self.installed.iter().map(|l| l.to_string()).collect();
+        let mut message = format!("{} {} installed for {}!", tool_name, version, launchers.join(", "));
+        if !self.failures.is_empty() {
+            message.push_str(&format!(" Failed: {}", self.failures.join("; ")));
+        }
+        if !self.warnings.is_empty() {
+            message.push_str(&format!(" Low disk space: {}", self.warnings.join("; ")));
+        }
+        Ok(message)
+    }
+}
+
+/// Total size of the regular files below `dir`, not following symlinks.
+pub fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| Some((entry.path(), entry.file_type().ok()?)))
+        .map(|(path, file_type)| {
+            if file_type.is_dir() {
+                dir_size(&path)
+            } else if file_type.is_file() {
+                std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0)
+            } else {
+                0
+            }
+        })
+        .sum()
+}
+
+/// Space needed to install an archive of `archive_size` bytes into `targets`,
+/// as `(directory, bytes)` pairs for [`check_space`].
+///
+/// The archive is unpacked into a staging directory next to the first target
+/// and the other targets are hardlinked to it where possible. Pass
+/// `download_dir`, the cache or temp directory, while the archive still has to
+/// be downloaded there.
+pub fn space_requirements(
+    archive_size: u64,
+    download_dir: Option<&Path>,
+    targets: &[(Launcher, PathBuf)],
+) -> Vec<(PathBuf, u64)> {
+    let unpacked = archive_size.saturating_mul(EXTRACTION_FACTOR);
+    let mut requirements = Vec::new();
+    if let Some(dir) = download_dir {
+        requirements.push((dir.to_path_buf(), archive_size));
+    }
+
+    if let Some((_, primary)) = targets.first() {
+        requirements.push((primary.clone(), unpacked));
+        for (_, path) in &targets[1..] {
+            if !same_filesystem(path, primary) {
+                requirements.push((path.clone(), unpacked));
+            }
+        }
+    }
+    requirements
+}
+
+/// Checks that `version` can name a runner directory: a single path component
+/// that is not `.` or `..`, so joining it to an install path stays inside it.
+pub fn check_version_name(version: &str) -> Result<()> {
+    let mut components = Path::new(version).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(name)), None) if name == version => Ok(()),
+        _ => Err(Error::Other(format!("Invalid version name: {:?}", version))),
+    }
+}
+
+/// Fails with [`Error::LauncherRunning`] while a process runs an executable from
+/// `dir`, e.g. a game started with the runner, as replacing or removing the
+/// files would break it.
+pub fn ensure_not_running(dir: &Path, launcher: &Launcher) -> Result<()> {
+    let Ok(dir) = dir.canonicalize() else {
+        return Ok(());
+    };
+    let Ok(processes) = std::fs::read_dir("/proc") else {
+        return Ok(());
+    };
+
+    let in_use = processes
+        .flatten()
+        .filter(|entry| entry.file_name().to_str().is_some_and(|name| name.bytes().all(|b| b.is_ascii_digit())))
+        .filter_map(|entry| std::fs::read_link(entry.path().join("exe")).ok())
+        .any(|exe| exe.starts_with(&dir));
+    if in_use {
+        return Err(Error::LauncherRunning { launcher: launcher.clone() });
+    }
+    Ok(())
+}
+
+/// Installs `source` as `version` into every `(launcher, install path)` target.
+///
+/// The source is unpacked once into the first target and replicated into the
+/// others. Each target is validated for its launcher; unusable ones are rolled
+/// back to the runner they replaced and reported, usable ones are recorded in
+/// `db` along with `origin` and the archive checksum.
+pub async fn install_to_targets<F>(
+    downloader: &Downloader,
+    db: &Database,
+    source: InstallSource<'_>,
+    origin: &RunnerSource,
+    version: &str,
+    targets: &[(Launcher, PathBuf)],
+    mut progress_callback: F,
+) -> Result<InstallReport>
+where
+    F: FnMut(String),
+{
+    check_version_name(version)?;
+    let (_, primary_path) = targets
+        .first()
+        .ok_or_else(|| Error::Other("No launcher selected".to_string()))?;
+
+    let mut origin = origin.clone();
+    if let InstallSource::Archive(archive_path) = source {
+        let archive_path = archive_path.to_path_buf();
+        origin.sha256 = Some(tokio::task::spawn_blocking(move || sha256_file(&archive_path)).await??);
+    }
+    
+    for (launcher, install_path) in targets {
+        ensure_not_running(&install_path.join(version), launcher)?;
+    }
+
+    // Stop before writing anything if a filesystem is too small
+    let requirements = match source {
+        InstallSource::Archive(archive_path) => {
+            let archive_size = std::fs::metadata(archive_path).map_err(|e| Error::io(e, archive_path))?.len();
+            space_requirements(archive_size, None, targets)
+        }
+        InstallSource::Directory(source_dir) => {
+            let source_dir = source_dir.to_path_buf();
+            let size = tokio::task::spawn_blocking(move || dir_size(&source_dir)).await?;
+            targets.iter().map(|(_, path)| (path.clone(), size)).collect()
+        }
+        InstallSource::Download { size, .. } => space_requirements(size.unwrap_or(0), None, targets),
+    };
+    let low_space = check_space(&requirements)?;
+
+    // Create install directories if they don't exist
+    for (_, install_path) in targets {
+        tokio::fs::create_dir_all(install_path)
+            .await
+            .map_err(|e| Error::io(e, install_path))?;
+    }
+
+    // Keep the runners being replaced until their replacements validate
+    let mut set_aside = Vec::new();
+    for (_, install_path) in targets {
+        let tool_dir = install_path.join(version);
+        if tool_dir.exists() {
+            let aside = Downloader::set_aside_path(&tool_dir);
+            let _ = tokio::fs::remove_dir_all(&aside).await;
+            if let Err(e) = tokio::fs::rename(&tool_dir, &aside).await {
+                put_back_all(&set_aside).await;
+                return Err(Error::io(e, &tool_dir));
+            }
+            set_aside.push((tool_dir, aside));
+        }
+    }
+
+    // Unpack once into the first launcher with specific directory name matching the version
+    let unpacked = match source {
+        InstallSource::Archive(archive_path) => {
+            progress_callback("Extracting (0%)".to_string());
+            downloader
+                .extract_archive_to_specific_dir(archive_path, primary_path, version, |progress| {
+                    progress_callback(format!("Extracting ({:.0}%)", progress.percent()));
+                })
+                .await
+        }
+        InstallSource::Directory(source_dir) => {
+            progress_callback("Copying...".to_string());
+            downloader.copy_dir_to_specific_dir(source_dir, primary_path, version).await
+        }
+        InstallSource::Download { url, mirrors, sha256, .. } => {
+            downloader
+                .stream_extract_to_specific_dir(url, mirrors, primary_path, version, sha256, |progress| {
+                    let amount = match progress.fraction() {
+                        Some(fraction) => format!("{:.0}%", fraction * 100.0),
+                        None => format_size(progress.downloaded),
+                    };
+                    progress_callback(format!("Downloading and extracting ({})", amount));
+                })
+                .await
+                .map(|actual| origin.sha256 = Some(actual))
+        }
+    };
+    if let Err(e) = unpacked {
+        put_back_all(&set_aside).await;
+        return Err(e.at(primary_path));
+    }
+    let primary_dir = primary_path.join(version);
+
+    // Without a size up front only the first target was checked, once the download started
+    if let InstallSource::Download { size: None, .. } = source {
+        let size_dir = primary_dir.clone();
+        let size = tokio::task::spawn_blocking(move || dir_size(&size_dir)).await?;
+        let replicas: Vec<_> = targets[1..]
+            .iter()
+            .filter(|(_, path)| !same_filesystem(path, primary_path))
+            .map(|(_, path)| (path.clone(), size))
+            .collect();
+        if let Err(e) = check_space(&replicas) {
+            let _ = tokio::fs::remove_dir_all(&primary_dir).await;
+            put_back_all(&set_aside).await;
+            return Err(e);
+        }
+    }
+
+    let mut report = InstallReport::default();
+    let mut primary_usable = false;
+
+    for (launcher, install_path) in targets {
+        let tool_dir = install_path.join(version);
+
+        // Fan the unpacked tree out to the other launchers
+        if tool_dir != primary_dir {
+            progress_callback(format!("Installing for {}...", launcher));
+            if let Err(e) = Downloader::replicate_dir(&primary_dir, &tool_dir).await {
+                let _ = tokio::fs::remove_dir_all(&tool_dir).await;
+                put_back(&set_aside, &tool_dir).await;
+                report.failures.push(format!("{}: {}", launcher, e));
+                continue;
+            }
+        }
+
+        // Roll back installs the launcher could not run
+        match validate_install(&tool_dir, launcher) {
+            Ok(()) => {
+                if tool_dir == primary_dir {
+                    primary_usable = true;
+                }
+                if let Some((_, aside)) = set_aside.iter().find(|(dir, _)| *dir == tool_dir) {
+                    let _ = tokio::fs::remove_dir_all(aside).await;
+                }
+                let mut record = InstalledRunner::new(version, launcher, &tool_dir, &origin);
+                let size_dir = tool_dir.clone();
+                record.installed_size = tokio::task::spawn_blocking(move || dir_size(&size_dir)).await.ok();
+                if let Err(e) = db.add_installed_runner(&record) {
+                    report.failures.push(format!("{} was installed for {} but could not be recorded: {}", version, launcher, e));
+                }
+                report.installed.push(launcher.clone());
+            }
+            Err(e) => {
+                // The first target is still needed by the others
+                if tool_dir != primary_dir {
+                    let _ = tokio::fs::remove_dir_all(&tool_dir).await;
+                    put_back(&set_aside, &tool_dir).await;
+                }
+                report.failures.push(format!("{} is not a usable {} runner: {}", version, launcher, e));
+            }
+        }
+    }
+
+    if !primary_usable {
+        let _ = tokio::fs::remove_dir_all(&primary_dir).await;
+        put_back(&set_aside, &primary_dir).await;
+    }
+    report.warnings = low_space.iter().map(|low| low.to_string()).collect();
+
+    Ok(report)
+}
+
+/// Moves the runner that `tool_dir` held before the install back in place.
+async fn put_back(set_aside: &[(PathBuf, PathBuf)], tool_dir: &Path) {
+    if let Some((_, aside)) = set_aside.iter().find(|(dir, _)| dir == tool_dir) {
+        let _ = tokio::fs::rename(aside, tool_dir).await;
+    }
+}
+
+async fn put_back_all(set_aside: &[(PathBuf, PathBuf)]) {
+    for (tool_dir, _) in set_aside {
+        put_back(set_aside, tool_dir).await;
+    }
+}
+
+/// Deletes `version` from each of `launchers` it is installed in and forgets
+/// it in `db`. Returns the launchers it was deleted from.
+///
+/// Stops at the first launcher still running it, keeping what was already deleted.
+pub async fn uninstall(
+    tool_manager: &ToolManager,
+    db: &Database,
+    version: &str,
+    launchers: &[Launcher],
+) -> Result<Vec<Launcher>> {
+    let mut deleted = Vec::new();
+    for launcher in launchers {
+        let Some(version_path) = tool_manager.find_install_dir(version, launcher) else {
+            continue;
+        };
+
+        // Delete the directory, unless a game is still running from it
+        ensure_not_running(&version_path, launcher)?;
+        tokio::fs::remove_dir_all(&version_path)
+            .await
+            .map_err(|e| Error::io(e, &version_path))?;
+        db.remove_installed_runner(&version_path)?;
+        deleted.push(launcher.clone());
+    }
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::test_util::{scratch_dir, tar_gz, write_steam_tool};
+
+    #[test]
+    fn test_default_version_name() {
+        let archive = Path::new("/tmp/GE-Proton9-20.tar.gz");
+        assert_eq!(InstallSource::Archive(archive).default_version_name().as_deref(), Some("GE-Proton9-20"));
+        let xz = Path::new("/tmp/wine-lutris-GE-Proton8-26-x86_64.tar.xz");
+        assert_eq!(
+            InstallSource::Archive(xz).default_version_name().as_deref(),
+            Some("wine-lutris-GE-Proton8-26-x86_64")
+        );
+        let dir = Path::new("/tmp/my-build");
+        assert_eq!(InstallSource::Directory(dir).default_version_name().as_deref(), Some("my-build"));
+        let download = InstallSource::Download {
+            url: "https://github.com/x/y/releases/download/GE-Proton9-20/GE-Proton9-20.tar.gz",
+            mirrors: &[],
+            sha256: None,
+            size: None,
+        };
+        assert_eq!(download.default_version_name().as_deref(), Some("GE-Proton9-20"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_ensure_not_running() {
+        // The test binary itself runs from the target directory
+        let exe_dir = std::env::current_exe().unwrap().parent().unwrap().to_path_buf();
+        let error = ensure_not_running(&exe_dir, &Launcher::Steam).unwrap_err();
+        assert!(matches!(error, Error::LauncherRunning { launcher: Launcher::Steam }));
+
+        let root = scratch_dir("installer-not-running");
+        assert!(ensure_not_running(&root, &Launcher::Steam).is_ok());
+        assert!(ensure_not_running(&root.join("missing"), &Launcher::Steam).is_ok());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_check_version_name() {
+        assert!(check_version_name("GE-Proton9-20").is_ok());
+        assert!(check_version_name("GE-Proton 9-20 (custom)").is_ok());
+        for name in ["", ".", "..", "../x", "/tmp/x", "a/b", "x/", "./x"] {
+            assert!(check_version_name(name).is_err(), "{:?} was accepted", name);
+        }
+    }
+
+    #[test]
+    fn test_space_requirements() {
+        let root = scratch_dir("installer-space");
+        let targets = vec![
+            (Launcher::Steam, root.join("steam")),
+            (Launcher::Lutris, root.join("lutris")),
+        ];
+        // Both targets share a filesystem, so the runner is unpacked once
+        let requirements = space_requirements(100, Some(&root), &targets);
+        assert_eq!(requirements, vec![(root.clone(), 100), (root.join("steam"), 100 * EXTRACTION_FACTOR)]);
+        assert!(space_requirements(100, None, &[]).is_empty());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_install_archive_to_targets() {
+        let root = scratch_dir("installer-archive");
+        let build = root.join("build/GE-Proton9-20");
+        write_steam_tool(&build);
+        let archive = root.join("GE-Proton9-20.tar.gz");
+        std::fs::write(&archive, tar_gz(&build)).unwrap();
+
+        let db = Database::open(&root.join("settings.db")).unwrap();
+        let targets = vec![
+            (Launcher::Steam, root.join("steam")),
+            (Launcher::Lutris, root.join("lutris")),
+        ];
+        let report = install_to_targets(
+            &Downloader::new(),
+            &db,
+            InstallSource::Archive(&archive),
+            &RunnerSource { tool_name: Some("GE-Proton".to_string()), ..Default::default() },
+            "GE-Proton9-20",
+            &targets,
+            |_| {},
+        ).await.unwrap();
+
+        assert_eq!(report.installed, vec![Launcher::Steam]);
+        assert_eq!(report.failures.len(), 1);
+        assert!(report.failures[0].contains("Lutris"));
+        assert!(root.join("steam/GE-Proton9-20/proton").is_file());
+        assert!(!root.join("lutris/GE-Proton9-20").exists());
+        let steam_entries: Vec<_> = std::fs::read_dir(root.join("steam")).unwrap().flatten().map(|e| e.file_name()).collect();
+        assert_eq!(steam_entries, vec!["GE-Proton9-20"], "The staging directory is removed");
+        assert_eq!(
+            db.get_install_dirs("GE-Proton9-20", &Launcher::Steam).unwrap(),
+            vec![root.join("steam/GE-Proton9-20")]
+        );
+        let runners = db.list_installed_runners().unwrap();
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].source.tool_name.as_deref(), Some("GE-Proton"));
+        assert_eq!(runners[0].source.sha256, Some(sha256_file(&archive).unwrap()));
+        assert_eq!(runners[0].tool_family.as_deref(), Some("ge-proton"));
+        assert_eq!(runners[0].installed_size, Some(dir_size(&root.join("steam/GE-Proton9-20"))));
+        assert!(runners[0].installed_size.unwrap() > 0);
+        assert_eq!(runners[0].app_version.as_deref(), Some(env!("CARGO_PKG_VERSION")));
+        assert!(archive.exists(), "Local archives are left in place");
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_reinstall_keeps_previous() {
+        let root = scratch_dir("installer-reinstall");
+        let working = root.join("build/working/GE-Proton9-20");
+        write_steam_tool(&working);
+        let broken = root.join("build/broken/GE-Proton9-20");
+        std::fs::create_dir_all(&broken).unwrap();
+        std::fs::write(broken.join("README"), "no compatibilitytool.vdf").unwrap();
+
+        let db = Database::open(&root.join("settings.db")).unwrap();
+        let targets = vec![(Launcher::Steam, root.join("steam"))];
+        let install = |build: PathBuf| {
+            let db = &db;
+            let targets = &targets;
+            async move {
+                let source = InstallSource::Directory(&build);
+                install_to_targets(&Downloader::new(), db, source, &RunnerSource::default(), "GE-Proton9-20", targets, |_| {}).await
+            }
+        };
+        let steam_entries = || -> Vec<_> {
+            std::fs::read_dir(root.join("steam")).unwrap().flatten().map(|e| e.file_name()).collect()
+        };
+
+        assert_eq!(install(working.clone()).await.unwrap().installed, vec![Launcher::Steam]);
+        std::fs::write(root.join("steam/GE-Proton9-20/marker"), "first install").unwrap();
+
+        // The runner that worked is back in place after a build that does not
+        let report = install(broken).await.unwrap();
+        assert!(report.installed.is_empty());
+        assert_eq!(std::fs::read_to_string(root.join("steam/GE-Proton9-20/marker")).unwrap(), "first install");
+        assert_eq!(steam_entries(), vec!["GE-Proton9-20"]);
+
+        // A working build replaces it and nothing is left aside
+        assert_eq!(install(working).await.unwrap().installed, vec![Launcher::Steam]);
+        assert!(!root.join("steam/GE-Proton9-20/marker").exists());
+        assert_eq!(steam_entries(), vec!["GE-Proton9-20"]);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_install_directory_to_targets() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = scratch_dir("installer-directory");
+        let build = root.join("build/my-wine");
+        std::fs::create_dir_all(build.join("bin")).unwrap();
+        std::fs::write(build.join("bin/wine"), "").unwrap();
+        std::fs::set_permissions(build.join("bin/wine"), std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let db = Database::open(&root.join("settings.db")).unwrap();
+        let targets = vec![
+            (Launcher::Lutris, root.join("lutris")),
+            (Launcher::Bottles, root.join("bottles")),
+        ];
+        let source = InstallSource::from_path(&build);
+        let report = install_to_targets(&Downloader::new(), &db, source, &RunnerSource::default(), "my-wine", &targets, |_| {})
+            .await
+            .unwrap();
+
+        assert_eq!(report.installed, vec![Launcher::Lutris, Launcher::Bottles]);
+        assert!(report.failures.is_empty());
+        assert!(root.join("bottles/my-wine/bin/wine").is_file());
+        assert!(build.join("bin/wine").is_file(), "Source directory is left in place");
+        assert_eq!(
+            report.into_message("Local", "my-wine").unwrap(),
+            "Local my-wine installed for Lutris, Bottles!"
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,176 @@
+//! End-to-end tests of fetching, installing and deleting runners, against a
+//! local server standing in for the release APIs and download hosts.
+
+use std::path::PathBuf;
+
+use super::database::{Database, RunnerSource};
+use super::downloader::Downloader;
+use super::error::Error;
+use super::installer::{install_to_targets, uninstall, InstallSource};
+use super::network::{HttpClient, NetworkSettings};
+use super::test_util::{scratch_dir, sha256_hex, tar_gz, tar_xz, write_steam_tool, write_wine_runner, FixtureServer};
+use super::tool_manager::{ReleaseApis, ToolManager, ToolVersion};
+use super::Launcher;
+
+/// A scratch install with Steam and Lutris paths, wired to a fixture server.
+struct Fixture {
+    root: PathBuf,
+    server: FixtureServer,
+    db: Database,
+    tool_manager: ToolManager,
+    downloader: Downloader,
+}
+
+impl Fixture {
+    async fn new(name: &str) -> Self {
+        let root = scratch_dir(name);
+        let server = FixtureServer::start().await;
+        let db = Database::open(&root.join("settings.db")).unwrap();
+        db.set_steam_path(Some(&root.join("steam"))).unwrap();
+        db.set_lutris_path(Some(&root.join("lutris"))).unwrap();
+
+        let http = HttpClient::new(NetworkSettings { max_attempts: 1, ..Default::default() }).unwrap();
+        let apis = ReleaseApis { github: server.url(""), dawn_wine: server.url("/api/v1") };
+        let tool_manager = ToolManager::with_http_client(db.clone(), http.clone()).with_release_apis(apis);
+        let downloader = Downloader::with_http_client(http);
+        downloader.set_temp_dir(Some(root.join("tmp")));
+        Self { root, server, db, tool_manager, downloader }
+    }
+
+    /// Publishes one release of `repo` whose only asset is `archive`, served at `/downloads/{file}`.
+    fn publish(&self, repo: &str, tag: &str, file: &str, archive: &[u8]) {
+        self.server.add(
+            &format!("/repos/{}/releases", repo),
+            format!(
+                r#"[{{"tag_name": "{}", "assets": [{{"name": "{}", "browser_download_url": "{}", "size": {}, "digest": "sha256:{}"}}]}}]"#,
+                tag,
+                file,
+                self.server.url(&format!("/downloads/{}", file)),
+                archive.len(),
+                sha256_hex(archive),
+            ),
+        );
+        self.server.add(&format!("/downloads/{}", file), archive);
+    }
+
+    /// The newest version of `tool_name` in the fetched release lists.
+    async fn newest(&self, tool_name: &str) -> ToolVersion {
+        let tools = self.tool_manager.fetch_tools_with_versions().await.unwrap();
+        let tool = tools.into_iter().find(|tool| tool.name == tool_name).expect("tool listed");
+        tool.versions.into_iter().next().expect("version listed")
+    }
+
+    fn origin(tool_name: &str, version: &ToolVersion) -> RunnerSource {
+        RunnerSource {
+            tool_name: Some(tool_name.to_string()),
+            url: Some(version.download_url.clone()),
+            sha256: None,
+        }
+    }
+
+    fn cleanup(self) {
+        std::fs::remove_dir_all(&self.root).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_install_and_delete() {
+    let fixture = Fixture::new("e2e-install").await;
+    let build = fixture.root.join("build/GE-Proton9-20");
+    write_steam_tool(&build);
+    let archive = tar_gz(&build);
+    fixture.publish("GloriousEggroll/proton-ge-custom", "GE-Proton9-20", "GE-Proton9-20.tar.gz", &archive);
+
+    let version = fixture.newest("GE-Proton").await;
+    assert_eq!(version.version, "GE-Proton9-20");
+    assert_eq!(version.sha256, Some(sha256_hex(&archive)));
+    assert!(!fixture.tool_manager.install_index().is_installed(&version.version, &Launcher::Steam));
+
+    let fetched = fixture.downloader
+        .fetch_archive(&version.download_url, &[], version.sha256.as_deref(), |_| {})
+        .await
+        .unwrap();
+    let steam_path = fixture.tool_manager.get_install_path(&Launcher::Steam).unwrap();
+    let report = install_to_targets(
+        &fixture.downloader,
+        &fixture.db,
+        InstallSource::Archive(&fetched.path),
+        &Fixture::origin("GE-Proton", &version),
+        &version.version,
+        &[(Launcher::Steam, steam_path.clone())],
+        |_| {},
+    ).await.unwrap();
+    assert_eq!(report.installed, vec![Launcher::Steam]);
+
+    assert!(fixture.tool_manager.install_index().is_installed(&version.version, &Launcher::Steam));
+    assert_eq!(fixture.tool_manager.install_index().steam_display_name(&version.version).as_deref(), Some("GE-Proton9-20"));
+    let runners = fixture.db.list_installed_runners().unwrap();
+    assert_eq!(runners.len(), 1);
+    assert_eq!(runners[0].install_dir, steam_path.join("GE-Proton9-20"));
+    assert_eq!(runners[0].source.url.as_deref(), Some(version.download_url.as_str()));
+    assert_eq!(runners[0].source.sha256, version.sha256);
+
+    let deleted = uninstall(&fixture.tool_manager, &fixture.db, &version.version, &[Launcher::Steam, Launcher::Lutris])
+        .await
+        .unwrap();
+    assert_eq!(deleted, vec![Launcher::Steam]);
+    assert!(!steam_path.join("GE-Proton9-20").exists());
+    assert!(!fixture.tool_manager.install_index().is_installed(&version.version, &Launcher::Steam));
+    assert!(fixture.db.list_installed_runners().unwrap().is_empty());
+    fixture.cleanup();
+}
+
+#[tokio::test]
+async fn test_streaming_install_from_mirror() {
+    let fixture = Fixture::new("e2e-mirror").await;
+    let build = fixture.root.join("build/lutris-GE-Proton8-26-x86_64");
+    write_wine_runner(&build);
+    let archive = tar_xz(&build);
+    let file = "wine-lutris-GE-Proton8-26-x86_64.tar.xz";
+    fixture.publish("GloriousEggroll/wine-ge-custom", "GE-Proton8-26", file, &archive);
+
+    // The release host is down; one mirror serves other bytes, the next the right ones
+    let version = fixture.newest("Wine-GE").await;
+    fixture.server.remove(&format!("/downloads/{}", file));
+    fixture.server.add(&format!("/tampered/{}", file), b"tampered".to_vec());
+    fixture.server.add(&format!("/mirror/GE-Proton8-26/{}", file), archive);
+    fixture.tool_manager.set_mirrors("Wine-GE", &[fixture.server.url("/tampered/{file}")]).unwrap();
+    fixture.downloader.set_streaming(true);
+    assert!(fixture.downloader.streams_extraction());
+
+    let lutris_path = fixture.tool_manager.get_install_path(&Launcher::Lutris).unwrap();
+    let targets = [(Launcher::Lutris, lutris_path.clone())];
+    let install = |mirrors: Vec<String>| {
+        let fixture = &fixture;
+        let version = &version;
+        let targets = &targets;
+        async move {
+            let source = InstallSource::Download {
+                url: &version.download_url,
+                mirrors: &mirrors,
+                sha256: version.sha256.as_deref(),
+                size: version.size,
+            };
+            let origin = Fixture::origin("Wine-GE", version);
+            install_to_targets(&fixture.downloader, &fixture.db, source, &origin, &version.version, targets, |_| {}).await
+        }
+    };
+
+    // Nothing is installed from an archive that does not match the release checksum
+    let mirrors = fixture.tool_manager.mirror_urls("Wine-GE", &version.version, &version.download_url);
+    let error = install(mirrors).await.unwrap_err();
+    assert!(matches!(error, Error::MirrorsFailed { origins: 2, .. }), "{}", error);
+    assert!(!lutris_path.join(&version.version).exists());
+    assert!(fixture.db.list_installed_runners().unwrap().is_empty());
+
+    let templates = vec![fixture.server.url("/tampered/{file}"), fixture.server.url("/mirror/{version}/{file}")];
+    fixture.tool_manager.set_mirrors("Wine-GE", &templates).unwrap();
+    let mirrors = fixture.tool_manager.mirror_urls("Wine-GE", &version.version, &version.download_url);
+    let report = install(mirrors).await.unwrap();
+    assert_eq!(report.installed, vec![Launcher::Lutris]);
+    assert!(lutris_path.join("GE-Proton8-26/bin/wine").is_file());
+    let runners = fixture.db.list_installed_runners().unwrap();
+    assert_eq!(runners.len(), 1);
+    assert_eq!(runners[0].source.sha256, version.sha256);
+    fixture.cleanup();
+}
@@ -0,0 +1,397 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::database::{Database, InstalledRunner, RunnerSource};
+use super::downloader::Downloader;
+use super::installer::{check_version_name, ensure_not_running, install_to_targets, InstallSource};
+use super::tool_manager::expand_mirror_template;
+use super::Launcher;
+use super::error::Result;
+
+/// One runner of a manifest, identified by version and launcher.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    pub version: String,
+    pub launcher: Launcher,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+impl ManifestEntry {
+    fn matches(&self, runner: &InstalledRunner) -> bool {
+        self.version == runner.version && self.launcher == runner.launcher
+    }
+}
+
+/// A set of installed runners that can be reproduced on another machine.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunnerManifest {
+    #[serde(default, rename = "runner")]
+    pub runners: Vec<ManifestEntry>,
+}
+
+/// File format of a manifest, chosen by extension. Anything but `.json` is TOML.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestFormat {
+    Toml,
+    Json,
+}
+
+impl ManifestFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => ManifestFormat::Json,
+            _ => ManifestFormat::Toml,
+        }
+    }
+}
+
+impl RunnerManifest {
+    pub fn from_installed(runners: &[InstalledRunner]) -> Self {
+        Self {
+            runners: runners
+                .iter()
+                .map(|runner| ManifestEntry {
+                    tool: runner.source.tool_name.clone(),
+                    version: runner.version.clone(),
+                    launcher: runner.launcher.clone(),
+                    source_url: runner.source.url.clone(),
+                    sha256: runner.source.sha256.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_string(&self, format: ManifestFormat) -> Result<String> {
+        Ok(match format {
+            ManifestFormat::Toml => toml::to_string_pretty(self)?,
+            ManifestFormat::Json => serde_json::to_string_pretty(self)?,
+        })
+    }
+
+    /// Reads a manifest, refusing versions that do not name a single directory.
+    pub fn parse(contents: &str, format: ManifestFormat) -> Result<Self> {
+        let manifest: Self = match format {
+            ManifestFormat::Toml => toml::from_str(contents)?,
+            ManifestFormat::Json => serde_json::from_str(contents)?,
+        };
+        for entry in &manifest.runners {
+            check_version_name(&entry.version)?;
+        }
+        Ok(manifest)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_string(ManifestFormat::from_path(path))?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::parse(&contents, ManifestFormat::from_path(path))
+    }
+
+    /// What it takes to make `installed` match this manifest. Runners whose
+    /// recorded directory is gone count as missing. Removals are only planned
+    /// in `sync` mode.
+    pub fn plan(&self, installed: &[InstalledRunner], sync: bool) -> SyncPlan {
+        let present: Vec<&InstalledRunner> = installed
+            .iter()
+            .filter(|runner| runner.install_dir.exists())
+            .collect();
+
+        let install = self.runners
+            .iter()
+            .filter(|entry| !present.iter().any(|runner| entry.matches(runner)))
+            .cloned()
+            .collect();
+
+        let remove = if sync {
+            installed
+                .iter()
+                .filter(|runner| !self.runners.iter().any(|entry| entry.matches(runner)))
+                .cloned()
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        SyncPlan { install, remove }
+    }
+}
+
+/// Runners to install and remove to apply a manifest.
+#[derive(Debug, Default)]
+pub struct SyncPlan {
+    pub install: Vec<ManifestEntry>,
+    pub remove: Vec<InstalledRunner>,
+}
+
+impl SyncPlan {
+    pub fn is_empty(&self) -> bool {
+        self.install.is_empty() && self.remove.is_empty()
+    }
+}
+
+/// Outcome of applying a [`SyncPlan`], one `version (launcher)` string per runner.
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub installed: Vec<String>,
+    pub removed: Vec<String>,
+    pub failures: Vec<String>,
+}
+
+impl SyncReport {
+    pub fn message(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.installed.is_empty() {
+            parts.push(format!("Installed {}", self.installed.join(", ")));
+        }
+        if !self.removed.is_empty() {
+            parts.push(format!("Removed {}", self.removed.join(", ")));
+        }
+        if !self.failures.is_empty() {
+            parts.push(format!("Failed: {}", self.failures.join("; ")));
+        }
+        if parts.is_empty() {
+            "All runners are already in place".to_string()
+        } else {
+            parts.join(". ")
+        }
+    }
+}
+
+fn describe(version: &str, launcher: &Launcher) -> String {
+    format!("{} ({})", version, launcher)
+}
+
+/// Installs the missing runners of `plan` and removes the extra ones.
+///
+/// Entries sharing a version and URL are downloaded once and installed into all
+/// their launchers. Archives whose checksum does not match the manifest are not
+/// installed. `install_paths` maps each launcher to its runner directory.
+pub async fn apply_plan<F>(
+    plan: &SyncPlan,
+    downloader: &Downloader,
+    db: &Database,
+    install_paths: &[(Launcher, PathBuf)],
+    mut progress_callback: F,
+) -> Result<SyncReport>
+where
+    F: FnMut(String),
+{
+    let mut report = SyncReport::default();
+
+    for runner in &plan.remove {
+        progress_callback(format!("Removing {}...", runner.version));
+        if let Err(e) = ensure_not_running(&runner.install_dir, &runner.launcher) {
+            report.failures.push(format!("{}: {}", describe(&runner.version, &runner.launcher), e));
+            continue;
+        }
+        if runner.install_dir.exists() {
+            if let Err(e) = tokio::fs::remove_dir_all(&runner.install_dir).await {
+                report.failures.push(format!("{}: {}", describe(&runner.version, &runner.launcher), e));
+                continue;
+            }
+        }
+        db.remove_installed_runner(&runner.install_dir)?;
+        report.removed.push(describe(&runner.version, &runner.launcher));
+    }
+
+    // Group entries that can share one download
+    let mut groups: Vec<(&ManifestEntry, Vec<Launcher>)> = Vec::new();
+    for entry in &plan.install {
+        match groups.iter_mut().find(|(first, _)| first.version == entry.version && first.source_url == entry.source_url) {
+            Some((_, launchers)) => launchers.push(entry.launcher.clone()),
+            None => groups.push((entry, vec![entry.launcher.clone()])),
+        }
+    }
+
+    for (entry, launchers) in groups {
+        let failed = |reason: String| {
+            launchers.iter()
+                .map(|launcher| format!("{}: {}", describe(&entry.version, launcher), reason))
+                .collect::<Vec<_>>()
+        };
+
+        let Some(url) = entry.source_url.as_deref() else {
+            report.failures.extend(failed("no source URL in manifest".to_string()));
+            continue;
+        };
+
+        let targets: Vec<(Launcher, PathBuf)> = install_paths
+            .iter()
+            .filter(|(launcher, _)| launchers.contains(launcher))
+            .cloned()
+            .collect();
+        if targets.len() != launchers.len() {
+            report.failures.extend(failed("no install path for launcher".to_string()));
+            continue;
+        }
+
+        let origin = RunnerSource {
+            tool_name: entry.tool.clone(),
+            url: Some(url.to_string()),
+            sha256: None,
+        };
+
+        let mirrors: Vec<String> = match &entry.tool {
+            Some(tool) => db.get_mirrors(tool)?
+                .iter()
+                .map(|template| expand_mirror_template(template, tool, &entry.version, url))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        // Unpacking while downloading checks the checksum before anything is moved into place
+        if downloader.streams_extraction() {
+            progress_callback(format!("Installing {}...", entry.version));
+            let source = InstallSource::Download { url, mirrors: &mirrors, sha256: entry.sha256.as_deref(), size: None };
+            match install_to_targets(downloader, db, source, &origin, &entry.version, &targets, |_| {}).await {
+                Ok(install) => {
+                    report.installed.extend(install.installed.iter().map(|launcher| describe(&entry.version, launcher)));
+                    report.failures.extend(install.failures);
+                }
+                Err(e) => report.failures.extend(failed(e.to_string())),
+            }
+            continue;
+        }
+
+        progress_callback(format!("Downloading {}...", entry.version));
+        // Mismatching downloads are thrown away and the next mirror tried
+        let archive = match downloader.fetch_archive(url, &mirrors, entry.sha256.as_deref(), |_| {}).await {
+            Ok(archive) => archive,
+            Err(e) => {
+                report.failures.extend(failed(e.to_string()));
+                continue;
+            }
+        };
+
+        progress_callback(format!("Installing {}...", entry.version));
+        let result = install_to_targets(downloader, db, InstallSource::Archive(&archive.path), &origin, &entry.version, &targets, |_| {}).await;
+
+        if !archive.cached {
+            let _ = tokio::fs::remove_file(&archive.path).await;
+        }
+
+        match result {
+            Ok(install) => {
+                report.installed.extend(install.installed.iter().map(|launcher| describe(&entry.version, launcher)));
+                report.failures.extend(install.failures);
+            }
+            Err(e) => report.failures.extend(failed(e.to_string())),
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::test_util::scratch_dir;
+
+    fn runner(version: &str, launcher: Launcher, install_dir: &Path) -> InstalledRunner {
+        let source = RunnerSource {
+            tool_name: Some("GE-Proton".to_string()),
+            url: Some(format!("https://example.com/{}.tar.gz", version)),
+            sha256: Some("ab12".to_string()),
+        };
+        InstalledRunner::new(version, &launcher, install_dir, &source)
+    }
+
+    #[test]
+    fn test_round_trip_toml_and_json() {
+        let manifest = RunnerManifest::from_installed(&[
+            runner("GE-Proton9-20", Launcher::Steam, Path::new("/steam/GE-Proton9-20")),
+            InstalledRunner {
+                source: RunnerSource::default(),
+                ..runner("my-wine", Launcher::Lutris, Path::new("/lutris/my-wine"))
+            },
+        ]);
+
+        let toml = manifest.to_string(ManifestFormat::Toml).unwrap();
+        assert!(toml.contains("[[runner]]"));
+        assert!(toml.contains("launcher = \"Steam\""));
+        assert_eq!(RunnerManifest::parse(&toml, ManifestFormat::Toml).unwrap(), manifest);
+
+        let json = manifest.to_string(ManifestFormat::Json).unwrap();
+        assert_eq!(RunnerManifest::parse(&json, ManifestFormat::Json).unwrap(), manifest);
+
+        assert_eq!(ManifestFormat::from_path(Path::new("lab.JSON")), ManifestFormat::Json);
+        assert_eq!(ManifestFormat::from_path(Path::new("lab.toml")), ManifestFormat::Toml);
+    }
+
+    #[test]
+    fn test_plan() {
+        let root = scratch_dir("manifest-plan");
+        std::fs::create_dir_all(root.join("GE-Proton9-20")).unwrap();
+        let installed = vec![
+            runner("GE-Proton9-20", Launcher::Steam, &root.join("GE-Proton9-20")),
+            runner("GE-Proton9-10", Launcher::Steam, &root.join("deleted-by-hand")),
+            runner("GE-Proton8-1", Launcher::Lutris, &root),
+        ];
+        let manifest = RunnerManifest::from_installed(&[
+            runner("GE-Proton9-20", Launcher::Steam, &root),
+            runner("GE-Proton9-10", Launcher::Steam, &root),
+            runner("GE-Proton9-20", Launcher::Heroic, &root),
+        ]);
+
+        let plan = manifest.plan(&installed, false);
+        let install: Vec<_> = plan.install.iter().map(|e| (e.version.as_str(), e.launcher.clone())).collect();
+        assert_eq!(install, vec![("GE-Proton9-10", Launcher::Steam), ("GE-Proton9-20", Launcher::Heroic)]);
+        assert!(plan.remove.is_empty());
+
+        let plan = manifest.plan(&installed, true);
+        assert_eq!(plan.install.len(), 2);
+        let remove: Vec<_> = plan.remove.iter().map(|r| r.version.as_str()).collect();
+        assert_eq!(remove, vec!["GE-Proton8-1"]);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_parse_rejects_paths_as_versions() {
+        for version in ["../x", "/tmp/x"] {
+            let toml = format!("[[runner]]\nversion = {:?}\nlauncher = \"Steam\"\n", version);
+            assert!(RunnerManifest::parse(&toml, ManifestFormat::Toml).is_err(), "{} was accepted", version);
+            let json = format!(r#"{{"runner": [{{"version": {:?}, "launcher": "Steam"}}]}}"#, version);
+            assert!(RunnerManifest::parse(&json, ManifestFormat::Json).is_err(), "{} was accepted", version);
+        }
+        let toml = "[[runner]]\nversion = \"GE-Proton9-20\"\nlauncher = \"Steam\"\n";
+        assert_eq!(RunnerManifest::parse(toml, ManifestFormat::Toml).unwrap().runners.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_apply_sync_removes_extra_runners() {
+        let root = scratch_dir("manifest-apply");
+        let db = Database::open(&root.join("settings.db")).unwrap();
+        let extra = root.join("lutris/GE-Proton8-1");
+        std::fs::create_dir_all(&extra).unwrap();
+        db.add_installed_runner(&runner("GE-Proton8-1", Launcher::Lutris, &extra)).unwrap();
+
+        // A local runner without a source URL cannot be reinstalled from the manifest
+        let manifest = RunnerManifest {
+            runners: vec![ManifestEntry {
+                tool: None,
+                version: "my-wine".to_string(),
+                launcher: Launcher::Lutris,
+                source_url: None,
+                sha256: None,
+            }],
+        };
+        let plan = manifest.plan(&db.list_installed_runners().unwrap(), true);
+        let targets = vec![(Launcher::Lutris, root.join("lutris"))];
+        let report = apply_plan(&plan, &Downloader::new(), &db, &targets, |_| {}).await.unwrap();
+
+        assert_eq!(report.removed, vec!["GE-Proton8-1 (Lutris)"]);
+        assert!(report.installed.is_empty());
+        assert_eq!(report.failures, vec!["my-wine (Lutris): no source URL in manifest"]);
+        assert!(!extra.exists());
+        assert!(db.list_installed_runners().unwrap().is_empty());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,7 +1,24 @@
 pub mod tool_manager;
 pub mod downloader;
 pub mod database;
+pub mod version;
+pub mod vdf;
+pub mod compat_tool;
+pub mod validation;
+pub mod archive_cache;
+pub mod installer;
+pub mod manifest;
+pub mod paths;
+pub mod error;
+pub mod disk_space;
+pub mod network;
 
-pub use tool_manager::{ToolManager, ToolWithVersions, Launcher};
+#[cfg(test)]
+mod test_util;
+#[cfg(test)]
+mod integration_tests;
+
+pub use tool_manager::{ToolManager, ToolWithVersions, Launcher, ReleaseChannel};
 pub use downloader::Downloader;
-pub use database::Database;
+pub use database::{Database, RunnerSource};
+pub use error::{Error, Result};
@@ -0,0 +1,646 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use reqwest::header::{HeaderValue, CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE};
+use reqwest::StatusCode;
+use ring::rand::SecureRandom;
+
+use super::error::{check_response, Error, Result};
+
+/// Longest wait between two attempts, however many have failed.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Rate limit given on the command line, which overrides the saved one.
+static RATE_LIMIT_ARG: OnceLock<u64> = OnceLock::new();
+
+/// Sent with every request, GitHub rejects API calls without one.
+const USER_AGENT: &str = concat!("ProtonUp-GTK/", env!("CARGO_PKG_VERSION"));
+
+/// Timeouts, retries, proxy and certificates for all HTTP traffic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkSettings {
+    pub connect_timeout: Duration,
+    /// Longest wait for the next bytes of a response, not for the whole download
+    pub read_timeout: Duration,
+    /// Attempts per download, including the first
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubled for each one after it
+    pub initial_backoff: Duration,
+    /// e.g. `http://proxy.example.com:3128`; without one the `HTTP_PROXY`
+    /// family of environment variables is used
+    pub proxy: Option<String>,
+    /// Comma-separated hosts, domains and CIDR ranges reached without the proxy
+    pub no_proxy: Option<String>,
+    /// PEM file with root certificates to trust besides the system ones
+    pub ca_bundle: Option<PathBuf>,
+    /// Skips certificate checks entirely, only meant for testing
+    pub accept_invalid_certs: bool,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+            max_attempts: 4,
+            initial_backoff: Duration::from_secs(1),
+            proxy: None,
+            no_proxy: None,
+            ca_bundle: None,
+            accept_invalid_certs: false,
+        }
+    }
+}
+
+impl NetworkSettings {
+    /// HTTP client applying these settings.
+    pub fn client(&self) -> Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .connect_timeout(self.connect_timeout)
+            .read_timeout(self.read_timeout)
+            .danger_accept_invalid_certs(self.accept_invalid_certs);
+
+        if let Some(url) = self.proxy.as_deref().filter(|url| !url.trim().is_empty()) {
+            let proxy = reqwest::Proxy::all(url.trim())
+                .map_err(|e| Error::Other(format!("Invalid proxy URL {}: {}", url, e)))?
+                .no_proxy(self.no_proxy.as_deref().and_then(reqwest::NoProxy::from_string));
+            builder = builder.proxy(proxy);
+        }
+
+        if let Some(path) = &self.ca_bundle {
+            let pem = std::fs::read(path).map_err(|e| Error::io(e, path))?;
+            let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+                .map_err(|e| Error::Other(format!("Invalid CA bundle {}: {}", path.display(), e)))?;
+            if certificates.is_empty() {
+                return Err(Error::Other(format!("No certificates found in {}", path.display())));
+            }
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        Ok(builder.build()?)
+    }
+
+    /// Wait before retry number `retry`, counting from 1, with up to half of it
+    /// added at random so clients that failed together do not retry together.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let base = self.initial_backoff
+            .saturating_mul(1 << retry.saturating_sub(1).min(16))
+            .min(MAX_BACKOFF);
+        base + base.mul_f64(jitter() / 2.0)
+    }
+}
+
+/// The client built from the current [`NetworkSettings`], shared by API calls
+/// and downloads. Cheap to clone; clones see settings changes.
+#[derive(Clone)]
+pub struct HttpClient {
+    current: Arc<Mutex<(reqwest::Client, NetworkSettings)>>,
+}
+
+impl HttpClient {
+    pub fn new(settings: NetworkSettings) -> Result<Self> {
+        let client = settings.client()?;
+        Ok(Self { current: Arc::new(Mutex::new((client, settings))) })
+    }
+
+    /// Applies `settings` to the requests sent from now on. On error the
+    /// previous settings stay in effect.
+    pub fn configure(&self, settings: NetworkSettings) -> Result<()> {
+        let client = settings.client()?;
+        *self.current.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = (client, settings);
+        Ok(())
+    }
+
+    pub fn client(&self) -> reqwest::Client {
+        self.current().0
+    }
+
+    /// Client and settings for a new request.
+    pub fn current(&self) -> (reqwest::Client, NetworkSettings) {
+        self.current.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new(NetworkSettings::default()).unwrap_or_else(|_| Self {
+            current: Arc::new(Mutex::new((reqwest::Client::new(), NetworkSettings::default()))),
+        })
+    }
+}
+
+/// Download speed limit shared by all downloads, so it caps their total.
+/// Cheap to clone; clones share the limit, which can change while downloads run.
+#[derive(Clone, Default)]
+pub struct RateLimit {
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Default)]
+struct Bucket {
+    /// Zero means unlimited
+    bytes_per_sec: u64,
+    /// When the bytes received so far are paid for
+    paid_until: Option<Instant>,
+}
+
+impl RateLimit {
+    fn bucket(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        self.bucket.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Sets the limit in bytes per second, `None` for unlimited.
+    pub fn set(&self, bytes_per_sec: Option<u64>) {
+        let mut bucket = self.bucket();
+        bucket.bytes_per_sec = bytes_per_sec.unwrap_or(0);
+        // Debt from a previous, lower limit would otherwise still be paid off
+        bucket.paid_until = None;
+    }
+
+    pub fn get(&self) -> Option<u64> {
+        Some(self.bucket().bytes_per_sec).filter(|rate| *rate > 0)
+    }
+
+    /// Waits until `bytes` just received fit into the limit. Reading the
+    /// response slower than it arrives makes TCP slow the sender down.
+    pub async fn consume(&self, bytes: u64) {
+        let wait = {
+            let mut bucket = self.bucket();
+            if bucket.bytes_per_sec == 0 {
+                return;
+            }
+            let now = Instant::now();
+            let start = bucket.paid_until.map_or(now, |paid| paid.max(now));
+            let paid_until = start + Duration::from_secs_f64(bytes as f64 / bucket.bytes_per_sec as f64);
+            bucket.paid_until = Some(paid_until);
+            paid_until - now
+        };
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Takes `--limit-rate <KiB/s>` and `--limit-rate=<KiB/s>` out of the command
+/// line, returning the limit and the remaining arguments. Zero means unlimited.
+pub fn parse_rate_limit_arg(args: Vec<OsString>) -> Result<(Option<u64>, Vec<OsString>)> {
+    let mut limit = None;
+    let mut remaining = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+
+    let parse = |value: &std::ffi::OsStr| {
+        value.to_str().and_then(|value| value.parse::<u64>().ok()).ok_or_else(|| {
+            Error::Other(format!("--limit-rate expects KiB/s as a whole number, got {}", value.to_string_lossy()))
+        })
+    };
+    while let Some(arg) = args.next() {
+        if arg == "--limit-rate" {
+            let value = args.next().ok_or_else(|| Error::Other("--limit-rate requires a value in KiB/s".to_string()))?;
+            limit = Some(parse(&value)?);
+        } else if let Some(value) = super::paths::strip_arg_prefix(&arg, "--limit-rate=") {
+            limit = Some(parse(value)?);
+        } else {
+            remaining.push(arg);
+        }
+    }
+
+    Ok((limit, remaining))
+}
+
+/// Records the rate limit given on the command line, in KiB/s. Only the first call has an effect.
+pub fn init_rate_limit_arg(limit_kib: u64) {
+    let _ = RATE_LIMIT_ARG.set(limit_kib);
+}
+
+/// Rate limit given on the command line in KiB/s, if any.
+pub fn rate_limit_arg() -> Option<u64> {
+    RATE_LIMIT_ARG.get().copied()
+}
+
+/// Bytes fetched from each origin when probing which one is fastest.
+const PROBE_BYTES: u64 = 256 * 1024;
+
+/// Orders `urls` fastest first by timing the first [`PROBE_BYTES`] of each,
+/// all fetched at once. Origins that fail or time out go last, keeping their order.
+pub async fn rank_by_speed(client: &reqwest::Client, settings: &NetworkSettings, urls: Vec<String>) -> Vec<String> {
+    let timeout = settings.connect_timeout + settings.read_timeout;
+    let mut probes = tokio::task::JoinSet::new();
+    for (index, url) in urls.iter().enumerate() {
+        let client = client.clone();
+        let url = url.clone();
+        probes.spawn(async move {
+            let elapsed = tokio::time::timeout(timeout, probe(&client, &url)).await.ok().and_then(|probed| probed.ok());
+            (index, elapsed)
+        });
+    }
+
+    let mut timings = vec![None; urls.len()];
+    while let Some(probed) = probes.join_next().await {
+        if let Ok((index, elapsed)) = probed {
+            timings[index] = elapsed;
+        }
+    }
+
+    let mut ranked: Vec<(Option<Duration>, String)> = timings.into_iter().zip(urls).collect();
+    ranked.sort_by_key(|(elapsed, _)| elapsed.unwrap_or(Duration::MAX));
+    ranked.into_iter().map(|(_, url)| url).collect()
+}
+
+/// Time taken to receive the first [`PROBE_BYTES`] of `url`, or all of it if smaller.
+async fn probe(client: &reqwest::Client, url: &str) -> Result<Duration> {
+    let start = Instant::now();
+    let request = client.get(url).header(RANGE, format!("bytes=0-{}", PROBE_BYTES - 1));
+    let mut response = check_response(request.send().await?)?;
+    let mut received = 0;
+    while received < PROBE_BYTES {
+        match response.chunk().await? {
+            Some(chunk) => received += chunk.len() as u64,
+            None => break,
+        }
+    }
+    Ok(start.elapsed())
+}
+
+/// Random value between 0 and 1.
+fn jitter() -> f64 {
+    let mut bytes = [0u8; 4];
+    if ring::rand::SystemRandom::new().fill(&mut bytes).is_err() {
+        return 0.5;
+    }
+    u32::from_le_bytes(bytes) as f64 / u32::MAX as f64
+}
+
+/// The body of a GET request that is requested again from where it stopped
+/// when the connection fails, waiting longer before each attempt.
+///
+/// Resumed requests carry `If-Range` with the validator of the first response,
+/// and fail with [`Error::SourceChanged`] when the file was replaced since, as
+/// the parts would not fit together. Servers that ignore `Range` send the whole
+/// file again, in which case the part already received is skipped.
+pub struct RetryingBody<'a> {
+    client: &'a reqwest::Client,
+    settings: &'a NetworkSettings,
+    url: &'a str,
+    response: Option<reqwest::Response>,
+    /// Size from the first response, if the server sent one
+    total: Option<u64>,
+    /// Strong ETag, or else Last-Modified, of the first response
+    validator: Option<HeaderValue>,
+    received: u64,
+    /// Bytes to drop from the current response before handing any out
+    skip: u64,
+    attempts: u32,
+}
+
+impl<'a> RetryingBody<'a> {
+    /// Sends the request, retrying until a response arrives or the attempts run out.
+    pub async fn open(client: &'a reqwest::Client, settings: &'a NetworkSettings, url: &'a str) -> Result<Self> {
+        let mut body = Self {
+            client,
+            settings,
+            url,
+            response: None,
+            total: None,
+            validator: None,
+            received: 0,
+            skip: 0,
+            attempts: 0,
+        };
+        if let Err(error) = body.send().await {
+            body.retry(error).await?;
+        }
+        body.total = body.response.as_ref().and_then(|response| response.content_length());
+        Ok(body)
+    }
+
+    pub fn total(&self) -> Option<u64> {
+        self.total
+    }
+
+    /// The next part of the body, or `None` once all of it was received.
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>> {
+        loop {
+            let Some(response) = self.response.as_mut() else {
+                return Ok(None);
+            };
+            let error = match response.chunk().await {
+                Ok(Some(mut chunk)) => {
+                    if self.skip > 0 {
+                        let skipped = self.skip.min(chunk.len() as u64);
+                        self.skip -= skipped;
+                        chunk = chunk.slice(skipped as usize..);
+                        if chunk.is_empty() {
+                            continue;
+                        }
+                    }
+                    self.received += chunk.len() as u64;
+                    return Ok(Some(chunk));
+                }
+                Ok(None) if self.received >= self.total.unwrap_or(0) => {
+                    self.response = None;
+                    return Ok(None);
+                }
+                Ok(None) => Error::Interrupted { url: self.url.to_string() },
+                Err(e) => e.into(),
+            };
+            self.retry(error).await?;
+        }
+    }
+
+    /// Waits and requests the rest of the body, until it succeeds or `error`
+    /// turns out not to be worth retrying.
+    async fn retry(&mut self, mut error: Error) -> Result<()> {
+        loop {
+            if !error.is_retryable() {
+                return Err(error);
+            }
+            if self.attempts >= self.settings.max_attempts {
+                return Err(Error::RetriesExhausted { attempts: self.attempts, source: Box::new(error) });
+            }
+            tokio::time::sleep(self.settings.backoff(self.attempts)).await;
+            match self.send().await {
+                Ok(()) => return Ok(()),
+                // Asking again gets the new file, which cannot continue the old one
+                Err(e @ Error::SourceChanged { .. }) => return Err(e),
+                Err(e) => error = e,
+            }
+        }
+    }
+
+    /// One attempt at requesting everything not received yet.
+    async fn send(&mut self) -> Result<()> {
+        self.attempts += 1;
+        self.response = None;
+        let mut request = self.client.get(self.url);
+        if self.received > 0 {
+            request = request.header(RANGE, format!("bytes={}-", self.received));
+            if let Some(validator) = &self.validator {
+                request = request.header(IF_RANGE, validator.clone());
+            }
+        }
+        let response = check_response(request.send().await?)?;
+
+        if self.received == 0 {
+            let headers = response.headers();
+            // Weak ETags are not allowed in If-Range
+            self.validator = headers.get(ETAG)
+                .filter(|etag| !etag.as_bytes().starts_with(b"W/"))
+                .or_else(|| headers.get(LAST_MODIFIED))
+                .cloned();
+            self.skip = 0;
+        } else if response.status() == StatusCode::PARTIAL_CONTENT {
+            let range = response.headers().get(CONTENT_RANGE).and_then(|value| value.to_str().ok());
+            let fits = range.and_then(parse_content_range).is_some_and(|(start, total)| {
+                start == self.received && (total.is_none() || self.total.is_none() || total == self.total)
+            });
+            if !fits {
+                return Err(Error::SourceChanged { url: self.url.to_string() });
+            }
+            self.skip = 0;
+        } else {
+            // With a validator a full response means it no longer matches
+            let resized = response.content_length().is_some() && self.total.is_some() && response.content_length() != self.total;
+            if self.validator.is_some() || resized {
+                return Err(Error::SourceChanged { url: self.url.to_string() });
+            }
+            self.skip = self.received;
+        }
+        self.response = Some(response);
+        Ok(())
+    }
+}
+
+/// Start and complete length from a `Content-Range: bytes <start>-<end>/<total>`
+/// header. The length is `None` when the server sends `*`.
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, total) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+    let (start, _) = range.split_once('-')?;
+    let total = match total {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+    Some((start.parse().ok()?, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::test_util::{http_response, serve};
+    use std::sync::{Arc, Mutex};
+
+    fn settings() -> NetworkSettings {
+        NetworkSettings { max_attempts: 3, initial_backoff: Duration::from_millis(1), ..Default::default() }
+    }
+
+    async fn download(url: &str) -> Result<Vec<u8>> {
+        let client = settings().client()?;
+        let settings = settings();
+        let mut body = RetryingBody::open(&client, &settings, url).await?;
+        let mut bytes = Vec::new();
+        while let Some(chunk) = body.chunk().await? {
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(bytes)
+    }
+
+    fn range_start(head: &str) -> Option<usize> {
+        head.lines()
+            .find_map(|line| line.to_ascii_lowercase().strip_prefix("range: bytes=").map(str::to_string))
+            .and_then(|range| range.trim_end_matches('-').parse().ok())
+    }
+
+    #[tokio::test]
+    async fn test_retry_and_resume() {
+        let body: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let ranges = Arc::new(Mutex::new(Vec::new()));
+        let seen = ranges.clone();
+        let served = body.clone();
+        let url = serve(move |index, head| {
+            seen.lock().unwrap().push(range_start(head));
+            match index {
+                0 => http_response("503 Service Unavailable", &[], b"", 0),
+                // Drops the connection halfway through
+                1 => http_response("200 OK", &[], &served[..40_000], served.len()),
+                _ => {
+                    let start = range_start(head).unwrap_or(0);
+                    let content_range = ("Content-Range", format!("bytes {}-{}/{}", start, served.len() - 1, served.len()));
+                    http_response("206 Partial Content", &[content_range], &served[start..], served.len() - start)
+                }
+            }
+        }).await;
+
+        assert_eq!(download(&format!("{}/GE-Proton9-20.tar.gz", url)).await.unwrap(), body);
+        assert_eq!(*ranges.lock().unwrap(), vec![None, None, Some(40_000)]);
+    }
+
+    #[tokio::test]
+    async fn test_resume_when_source_changed() {
+        let old: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+        let new: Vec<u8> = (0..30_000u32).map(|i| (i % 241) as u8).collect();
+        let if_ranges = Arc::new(Mutex::new(Vec::new()));
+        let seen = if_ranges.clone();
+        let url = serve(move |index, head| {
+            let if_range = head.lines().find_map(|line| line.to_ascii_lowercase().strip_prefix("if-range: ").map(str::to_string));
+            seen.lock().unwrap().push(if_range.clone());
+            let etag = ("ETag", "\"v1\"".to_string());
+            match (index, head.contains("/shifted")) {
+                (0, _) | (2, _) => http_response("200 OK", &[etag], &old[..5_000], old.len()),
+                // The validator no longer matches, so the new file is sent in full
+                (1, false) => http_response("200 OK", &[("ETag", "\"v2\"".to_string())], &new, new.len()),
+                // A range that does not continue where the download stopped
+                _ => {
+                    let content_range = ("Content-Range", format!("bytes 0-{}/{}", old.len() - 1, old.len()));
+                    http_response("206 Partial Content", &[content_range], &old, old.len())
+                }
+            }
+        }).await;
+
+        let error = download(&format!("{}/GE-Proton9-20.tar.gz", url)).await.unwrap_err();
+        assert!(matches!(error, Error::SourceChanged { .. }), "{}", error);
+        assert_eq!(*if_ranges.lock().unwrap(), vec![None, Some("\"v1\"".to_string())]);
+
+        let error = download(&format!("{}/shifted.tar.gz", url)).await.unwrap_err();
+        assert!(matches!(error, Error::SourceChanged { .. }), "{}", error);
+    }
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(parse_content_range("bytes 40000-99999/100000"), Some((40_000, Some(100_000))));
+        assert_eq!(parse_content_range("bytes 0-9/*"), Some((0, None)));
+        assert_eq!(parse_content_range("bytes */100000"), None);
+    }
+
+    #[tokio::test]
+    async fn test_resume_without_range_support() {
+        let body: Vec<u8> = (0..50_000u32).map(|i| (i % 241) as u8).collect();
+        let served = body.clone();
+        let url = serve(move |index, _| match index {
+            0 => http_response("200 OK", &[], &served[..10_000], served.len()),
+            _ => http_response("200 OK", &[], &served, served.len()),
+        }).await;
+
+        assert_eq!(download(&format!("{}/wine-ge.tar.xz", url)).await.unwrap(), body);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up() {
+        let requests = Arc::new(Mutex::new(0));
+        let count = requests.clone();
+        let url = serve(move |_, head| {
+            *count.lock().unwrap() += 1;
+            if head.contains("/missing") {
+                http_response("404 Not Found", &[], b"", 0)
+            } else {
+                http_response("500 Internal Server Error", &[], b"", 0)
+            }
+        }).await;
+
+        let error = download(&format!("{}/broken.tar.gz", url)).await.unwrap_err();
+        assert!(matches!(&error, Error::RetriesExhausted { attempts: 3, source } if matches!(**source, Error::HttpStatus { status: 500, .. })));
+        assert!(error.to_string().starts_with("Download failed after 3 attempts"));
+        assert_eq!(*requests.lock().unwrap(), 3);
+
+        // Errors retrying cannot fix are returned at once
+        let error = download(&format!("{}/missing.tar.gz", url)).await.unwrap_err();
+        assert!(matches!(error, Error::HttpStatus { status: 404, .. }));
+        assert_eq!(*requests.lock().unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_proxy() {
+        let heads = Arc::new(Mutex::new(Vec::new()));
+        let seen = heads.clone();
+        let proxy = serve(move |_, head| {
+            seen.lock().unwrap().push(head.lines().next().unwrap_or_default().to_string());
+            http_response("200 OK", &[], b"ok", 2)
+        }).await;
+
+        let http = HttpClient::new(NetworkSettings {
+            proxy: Some(proxy.clone()),
+            no_proxy: Some("localhost".to_string()),
+            ..settings()
+        }).unwrap();
+        let body = http.client().get("http://releases.invalid/GE-Proton9-20.tar.gz").send().await.unwrap();
+        assert_eq!(body.text().await.unwrap(), "ok");
+        assert_eq!(*heads.lock().unwrap(), vec!["GET http://releases.invalid/GE-Proton9-20.tar.gz HTTP/1.1"]);
+
+        // Hosts on the bypass list are contacted directly
+        let direct = proxy.replace("127.0.0.1", "localhost");
+        assert!(http.client().get(format!("{}/direct", direct)).send().await.is_ok());
+        assert_eq!(heads.lock().unwrap().last().unwrap(), "GET /direct HTTP/1.1");
+
+        let invalid = NetworkSettings { proxy: Some("not a url".to_string()), ..settings() };
+        assert!(http.configure(invalid).is_err());
+        assert!(http.current().1.proxy.is_some(), "The previous settings stay in effect");
+    }
+
+    #[test]
+    fn test_ca_bundle() {
+        let root = crate::backend::test_util::scratch_dir("ca-bundle");
+        let missing = NetworkSettings { ca_bundle: Some(root.join("missing.pem")), ..Default::default() };
+        assert!(matches!(missing.client(), Err(Error::Io(_))));
+
+        std::fs::write(root.join("empty.pem"), "not a certificate").unwrap();
+        let empty = NetworkSettings { ca_bundle: Some(root.join("empty.pem")), ..Default::default() };
+        assert!(empty.client().unwrap_err().to_string().starts_with("No certificates found"));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit() {
+        let limit = RateLimit::default();
+        let start = Instant::now();
+        limit.consume(100_000_000).await;
+        assert!(start.elapsed() < Duration::from_millis(50), "Unlimited by default");
+
+        limit.set(Some(1_000_000));
+        assert_eq!(limit.get(), Some(1_000_000));
+        let start = Instant::now();
+        for _ in 0..5 {
+            limit.consume(50_000).await;
+        }
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(240) && elapsed < Duration::from_secs(2), "took {:?}", elapsed);
+
+        limit.set(None);
+        assert_eq!(limit.get(), None);
+    }
+
+    #[test]
+    fn test_parse_rate_limit_arg() {
+        let args = |values: &[&str]| values.iter().map(OsString::from).collect::<Vec<_>>();
+        let (limit, rest) = parse_rate_limit_arg(args(&["protonup-gtk", "--limit-rate", "2048", "--portable"])).unwrap();
+        assert_eq!(limit, Some(2048));
+        assert_eq!(rest, args(&["protonup-gtk", "--portable"]));
+        assert_eq!(parse_rate_limit_arg(args(&["protonup-gtk", "--limit-rate=0"])).unwrap().0, Some(0));
+        assert_eq!(parse_rate_limit_arg(args(&["protonup-gtk"])).unwrap().0, None);
+        assert!(parse_rate_limit_arg(args(&["protonup-gtk", "--limit-rate", "fast"])).is_err());
+        assert!(parse_rate_limit_arg(args(&["protonup-gtk", "--limit-rate"])).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rank_by_speed() {
+        let good = serve(|_, _| http_response("200 OK", &[], b"runner", 6)).await;
+        let missing = serve(|_, _| http_response("404 Not Found", &[], b"", 0)).await;
+        let urls = vec![format!("{}/a.tar.gz", missing), format!("{}/a.tar.gz", good), "http://127.0.0.1:1/a.tar.gz".to_string()];
+
+        let client = settings().client().unwrap();
+        let ranked = rank_by_speed(&client, &settings(), urls.clone()).await;
+        // The one answering comes first, the failing ones keep their order
+        assert_eq!(ranked, vec![urls[1].clone(), urls[0].clone(), urls[2].clone()]);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let settings = NetworkSettings { initial_backoff: Duration::from_secs(2), ..Default::default() };
+        for (retry, base) in [(1, 2), (2, 4), (3, 8), (10, 60)] {
+            let wait = settings.backoff(retry);
+            let base = Duration::from_secs(base);
+            assert!(wait >= base && wait <= base + base / 2, "retry {} waited {:?}", retry, wait);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use super::error::{Error, Result};
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+pub const APP_ID: &str = "com.github.Mar0xy.ProtonUpGtk";
+
+/// Directory name used before it was aligned with the app ID.
+const LEGACY_DIR_NAME: &str = "com.github.Mar0xy.ProtonUpGTK";
+
+/// Overrides where settings and caches are kept.
+pub const DATA_DIR_ENV: &str = "PROTONUP_GTK_DATA_DIR";
+/// Any value but `0` or an empty string enables portable mode.
+pub const PORTABLE_ENV: &str = "PROTONUP_GTK_PORTABLE";
+/// A file with this name next to the executable enables portable mode.
+pub const PORTABLE_MARKER: &str = "portable.txt";
+/// Data directory created next to the executable in portable mode.
+const PORTABLE_DIR_NAME: &str = "protonup-gtk-data";
+
+/// Where the settings database and caches live.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataLocation {
+    /// The XDG config and cache directories
+    Default,
+    /// Settings in the directory itself, caches in its `cache` subdirectory
+    Custom(PathBuf),
+}
+
+static LOCATION: OnceLock<DataLocation> = OnceLock::new();
+
+impl DataLocation {
+    /// `protonup-gtk-data` next to the running executable.
+    pub fn portable() -> Result<Self> {
+        let exe = std::env::current_exe()?;
+        let exe_dir = exe
+            .parent()
+            .ok_or_else(|| Error::Other("Could not determine executable directory".to_string()))?;
+        Ok(DataLocation::Custom(exe_dir.join(PORTABLE_DIR_NAME)))
+    }
+
+    /// Location from the environment or a portable marker, falling back to the default.
+    fn detect() -> Self {
+        if let Some(dir) = std::env::var_os(DATA_DIR_ENV).filter(|dir| !dir.is_empty()) {
+            return DataLocation::Custom(PathBuf::from(dir));
+        }
+
+        let portable_env = std::env::var(PORTABLE_ENV).is_ok_and(|value| !value.is_empty() && value != "0");
+        let portable_marker = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(|dir| dir.join(PORTABLE_MARKER).is_file()))
+            .unwrap_or(false);
+        if portable_env || portable_marker {
+            if let Ok(location) = Self::portable() {
+                return location;
+            }
+        }
+
+        DataLocation::Default
+    }
+
+    pub fn config_dir(&self) -> Result<PathBuf> {
+        match self {
+            DataLocation::Custom(dir) => Ok(dir.clone()),
+            DataLocation::Default => migrate_legacy_dir(
+                &dirs::config_dir().ok_or_else(|| Error::Other("Could not determine config directory".to_string()))?,
+            ),
+        }
+    }
+
+    pub fn cache_dir(&self) -> Result<PathBuf> {
+        match self {
+            DataLocation::Custom(dir) => Ok(dir.join("cache")),
+            DataLocation::Default => migrate_legacy_dir(
+                &dirs::cache_dir().ok_or_else(|| Error::Other("Could not determine cache directory".to_string()))?,
+            ),
+        }
+    }
+}
+
+/// Sets the location for this process, e.g. from command line flags. Has no
+/// effect once the location has been used.
+pub fn init(location: DataLocation) {
+    let _ = LOCATION.set(location);
+}
+
+/// The location set by [`init`], or the one detected from the environment.
+pub fn location() -> &'static DataLocation {
+    LOCATION.get_or_init(DataLocation::detect)
+}
+
+/// `<base>/com.github.Mar0xy.ProtonUpGtk`, moving the directory over from its
+/// old name if only that one exists.
+pub fn migrate_legacy_dir(base: &Path) -> Result<PathBuf> {
+    let dir = base.join(APP_ID);
+    let legacy = base.join(LEGACY_DIR_NAME);
+
+    // On case-insensitive filesystems both names are the same directory and `dir` exists
+    if !dir.exists() && legacy.is_dir() {
+        std::fs::rename(&legacy, &dir)?;
+    }
+    Ok(dir)
+}
+
+/// Takes `--data-dir <path>`, `--data-dir=<path>` and `--portable` out of the
+/// command line, returning the location they select and the remaining arguments.
+/// Arguments need not be UTF-8, so any path the system allows can be given.
+pub fn parse_args(args: Vec<OsString>) -> Result<(Option<DataLocation>, Vec<OsString>)> {
+    let mut location = None;
+    let mut remaining = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--portable" {
+            location = Some(DataLocation::portable()?);
+        } else if arg == "--data-dir" {
+            let dir = args.next().ok_or_else(|| Error::Other("--data-dir requires a path".to_string()))?;
+            location = Some(DataLocation::Custom(PathBuf::from(dir)));
+        } else if let Some(dir) = strip_arg_prefix(&arg, "--data-dir=") {
+            location = Some(DataLocation::Custom(PathBuf::from(dir)));
+        } else {
+            remaining.push(arg);
+        }
+    }
+
+    Ok((location, remaining))
+}
+
+/// The value of a `--name=value` argument given its `--name=` prefix.
+pub fn strip_arg_prefix<'a>(arg: &'a OsStr, prefix: &str) -> Option<&'a OsStr> {
+    let value = arg.as_encoded_bytes().strip_prefix(prefix.as_bytes())?;
+    // SAFETY: the bytes are split directly after a valid UTF-8 prefix
+    Some(unsafe { OsStr::from_encoded_bytes_unchecked(value) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::test_util::scratch_dir;
+
+    fn args(values: &[&str]) -> Vec<OsString> {
+        values.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let (location, rest) = parse_args(args(&["protonup-gtk", "--data-dir", "/mnt/usb/data", "--gapplication-service"])).unwrap();
+        assert_eq!(location, Some(DataLocation::Custom(PathBuf::from("/mnt/usb/data"))));
+        assert_eq!(rest, args(&["protonup-gtk", "--gapplication-service"]));
+
+        let (location, _) = parse_args(args(&["protonup-gtk", "--data-dir=/tmp/x"])).unwrap();
+        assert_eq!(location, Some(DataLocation::Custom(PathBuf::from("/tmp/x"))));
+
+        let (location, rest) = parse_args(args(&["protonup-gtk", "--portable"])).unwrap();
+        let Some(DataLocation::Custom(dir)) = location else {
+            panic!("--portable should select a custom location");
+        };
+        assert!(dir.ends_with(PORTABLE_DIR_NAME));
+        assert_eq!(rest, args(&["protonup-gtk"]));
+
+        assert_eq!(parse_args(args(&["protonup-gtk"])).unwrap().0, None);
+        assert!(parse_args(args(&["protonup-gtk", "--data-dir"])).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_args_non_utf8() {
+        use std::os::unix::ffi::OsStringExt;
+        let dir = OsString::from_vec(b"/mnt/\xffdata".to_vec());
+        let mut joined = OsString::from("--data-dir=");
+        joined.push(&dir);
+
+        let (location, _) = parse_args(vec!["protonup-gtk".into(), "--data-dir".into(), dir.clone()]).unwrap();
+        assert_eq!(location, Some(DataLocation::Custom(PathBuf::from(&dir))));
+        let (location, _) = parse_args(vec!["protonup-gtk".into(), joined]).unwrap();
+        assert_eq!(location, Some(DataLocation::Custom(PathBuf::from(&dir))));
+    }
+
+    #[test]
+    fn test_custom_location_dirs() {
+        let location = DataLocation::Custom(PathBuf::from("/mnt/usb/data"));
+        assert_eq!(location.config_dir().unwrap(), PathBuf::from("/mnt/usb/data"));
+        assert_eq!(location.cache_dir().unwrap(), PathBuf::from("/mnt/usb/data/cache"));
+    }
+
+    #[test]
+    fn test_migrate_legacy_dir() {
+        let root = scratch_dir("paths-legacy");
+        std::fs::create_dir_all(root.join(LEGACY_DIR_NAME)).unwrap();
+        std::fs::write(root.join(LEGACY_DIR_NAME).join("settings.db"), "db").unwrap();
+
+        let dir = migrate_legacy_dir(&root).unwrap();
+        assert_eq!(dir, root.join(APP_ID));
+        assert_eq!(std::fs::read_to_string(dir.join("settings.db")).unwrap(), "db");
+
+        // Once moved, later calls leave the directory alone
+        assert_eq!(migrate_legacy_dir(&root).unwrap(), dir);
+        assert!(dir.join("settings.db").is_file());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Fresh scratch directory under the system temp dir, unique per test name and process.
+pub fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("protonup-test-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Failed to create scratch dir");
+    dir
+}
+
+/// Serves HTTP on a local port for download tests and returns its base URL.
+///
+/// `respond` gets the number of the request, counting from 0, and its head,
+/// and returns the raw response. Each connection is closed after one response,
+/// so a body shorter than its `Content-Length` looks like a dropped connection.
+pub async fn serve<F>(respond: F) -> String
+where
+    F: Fn(usize, &str) -> Vec<u8> + Send + Sync + 'static,
+{
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("Failed to bind test server");
+    let address = listener.local_addr().unwrap();
+    let respond = Arc::new(respond);
+    tokio::spawn(async move {
+        for index in 0.. {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                return;
+            };
+            let respond = respond.clone();
+            tokio::spawn(async move {
+                let mut head = Vec::new();
+                let mut buffer = [0u8; 1024];
+                while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+                    match stream.read(&mut buffer).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(read) => head.extend_from_slice(&buffer[..read]),
+                    }
+                }
+                let response = respond(index, &String::from_utf8_lossy(&head));
+                let _ = stream.write_all(&response).await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+    format!("http://{}", address)
+}
+
+/// Raw HTTP response announcing `content_length` bytes and sending `body`.
+pub fn http_response(status: &str, headers: &[(&str, String)], body: &[u8], content_length: usize) -> Vec<u8> {
+    let mut response = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n", status, content_length);
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    let mut response = response.into_bytes();
+    response.extend_from_slice(body);
+    response
+}
+
+/// Local server answering GET requests from a table of paths, standing in for
+/// the release APIs and their download hosts in end-to-end tests.
+///
+/// Paths are matched without their query string; unknown ones get a 404.
+pub struct FixtureServer {
+    base_url: String,
+    routes: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+}
+
+impl FixtureServer {
+    pub async fn start() -> Self {
+        let routes: Arc<Mutex<HashMap<String, Vec<u8>>>> = Arc::default();
+        let served = routes.clone();
+        let base_url = serve(move |_, head| {
+            let path = head.split_whitespace().nth(1).unwrap_or("/");
+            let path = path.split('?').next().unwrap_or(path);
+            match served.lock().unwrap().get(path) {
+                Some(body) => http_response("200 OK", &[], body, body.len()),
+                None => http_response("404 Not Found", &[], b"", 0),
+            }
+        }).await;
+        Self { base_url, routes }
+    }
+
+    /// Absolute URL of `path` on this server.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Serves `body` at `path` from now on.
+    pub fn add(&self, path: &str, body: impl Into<Vec<u8>>) {
+        self.routes.lock().unwrap().insert(path.to_string(), body.into());
+    }
+
+    /// Answers `path` with a 404 from now on.
+    pub fn remove(&self, path: &str) {
+        self.routes.lock().unwrap().remove(path);
+    }
+}
+
+/// A minimal Proton build Steam accepts, named after the directory.
+pub fn write_steam_tool(dir: &Path) {
+    let name = dir.file_name().unwrap().to_str().unwrap();
+    std::fs::create_dir_all(dir.join("files/bin")).unwrap();
+    std::fs::write(dir.join("proton"), "#!/usr/bin/env python3\n").unwrap();
+    std::fs::write(
+        dir.join("compatibilitytool.vdf"),
+        format!(r#""compatibilitytools" {{ "compat_tools" {{ "{0}" {{ "install_path" "." "display_name" "{0}" "from_oslist" "windows" "to_oslist" "linux" }} }} }}"#, name),
+    ).unwrap();
+    std::fs::write(
+        dir.join("toolmanifest.vdf"),
+        r#""manifest" { "version" "2" "commandline" "/proton %verb%" }"#,
+    ).unwrap();
+}
+
+/// A minimal Wine build Lutris accepts.
+pub fn write_wine_runner(dir: &Path) {
+    std::fs::create_dir_all(dir.join("bin")).unwrap();
+    std::fs::write(dir.join("bin/wine"), "#!/bin/sh\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(dir.join("bin/wine"), std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+}
+
+/// `source_dir` packed as a `.tar.gz`, with the directory itself at the top.
+pub fn tar_gz(source_dir: &Path) -> Vec<u8> {
+    let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    let mut builder = tar::Builder::new(encoder);
+    builder.append_dir_all(source_dir.file_name().unwrap(), source_dir).unwrap();
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+/// `source_dir` packed as a `.tar.xz`, with the directory itself at the top.
+pub fn tar_xz(source_dir: &Path) -> Vec<u8> {
+    let encoder = xz2::write::XzEncoder::new(Vec::new(), 1);
+    let mut builder = tar::Builder::new(encoder);
+    builder.append_dir_all(source_dir.file_name().unwrap(), source_dir).unwrap();
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+/// Hex SHA-256 of `bytes`, as GitHub publishes it in asset digests.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+    context.update(bytes);
+    crate::backend::archive_cache::hex_digest(context)
+}
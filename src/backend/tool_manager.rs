@@ -1,11 +1,27 @@
 use serde::{Deserialize, Serialize};
-use anyhow::Result;
-use reqwest::Client;
+use super::error::{check_response, Error, Result};
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Launcher {
     Steam,
     Lutris,
+    Heroic,
+    Bottles,
+}
+
+impl Launcher {
+    pub const ALL: [Launcher; 4] = [Launcher::Steam, Launcher::Lutris, Launcher::Heroic, Launcher::Bottles];
+
+    /// Default directory runners are installed to, relative to the home directory.
+    pub fn default_relative_path(&self) -> &'static str {
+        match self {
+            Launcher::Steam => ".steam/root/compatibilitytools.d",
+            Launcher::Lutris => ".local/share/lutris/runners/wine",
+            Launcher::Heroic => ".config/heroic/tools/wine",
+            Launcher::Bottles => ".local/share/bottles/runners",
+        }
+    }
 }
 
 impl std::fmt::Display for Launcher {
@@ -13,6 +29,50 @@ impl std::fmt::Display for Launcher {
         match self {
             Launcher::Steam => write!(f, "Steam"),
             Launcher::Lutris => write!(f, "Lutris"),
+            Launcher::Heroic => write!(f, "Heroic"),
+            Launcher::Bottles => write!(f, "Bottles"),
+        }
+    }
+}
+
+impl std::str::FromStr for Launcher {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        Launcher::ALL
+            .into_iter()
+            .find(|launcher| launcher.to_string().eq_ignore_ascii_case(value))
+            .ok_or_else(|| Error::Other(format!("Unknown launcher: {}", value)))
+    }
+}
+
+/// Which releases of a source are offered for installation.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum ReleaseChannel {
+    /// Only regular releases.
+    #[default]
+    Stable,
+    /// Regular releases plus those flagged as pre-releases upstream.
+    PreRelease,
+}
+
+impl ReleaseChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReleaseChannel::Stable => "stable",
+            ReleaseChannel::PreRelease => "prerelease",
+        }
+    }
+}
+
+impl std::str::FromStr for ReleaseChannel {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "stable" => Ok(ReleaseChannel::Stable),
+            "prerelease" => Ok(ReleaseChannel::PreRelease),
+            _ => Err(Error::Other(format!("Unknown release channel: {}", value))),
         }
     }
 }
@@ -21,13 +81,38 @@ impl std::fmt::Display for Launcher {
 #[derive(Debug, Deserialize)]
 struct GitHubRelease {
     tag_name: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
     assets: Vec<GitHubAsset>,
 }
 
+impl GitHubRelease {
+    /// Drafts are never offered; pre-releases only when the channel allows them.
+    fn is_allowed(&self, channel: ReleaseChannel) -> bool {
+        if self.draft {
+            return false;
+        }
+        !self.prerelease || channel == ReleaseChannel::PreRelease
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct GitHubAsset {
     name: String,
     browser_download_url: String,
+    #[serde(default)]
+    size: Option<u64>,
+    /// `sha256:<hex>` on GitHub, missing on older releases and other forges
+    #[serde(default)]
+    digest: Option<String>,
+}
+
+impl GitHubAsset {
+    fn sha256(&self) -> Option<String> {
+        self.digest.as_deref()?.strip_prefix("sha256:").map(|hex| hex.to_ascii_lowercase())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -42,78 +127,134 @@ pub struct ToolWithVersions {
 pub struct ToolVersion {
     pub version: String,
     pub download_url: String,
+    /// Archive size in bytes as reported by the release API
+    pub size: Option<u64>,
+    /// Archive checksum published with the release, checked whichever mirror serves it
+    pub sha256: Option<String>,
+    pub prerelease: bool,
+}
+
+impl ToolVersion {
+    pub fn parsed(&self) -> Option<Version> {
+        Version::parse(&self.version)
+    }
+}
+
+/// Sorts newest first; versions that cannot be parsed keep their API order at the end.
+fn sort_newest_first(versions: &mut [ToolVersion]) {
+    versions.sort_by(|a, b| match (a.parsed(), b.parsed()) {
+        (Some(a), Some(b)) => b.cmp(&a),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
 }
 
-use super::database::Database;
+use std::collections::HashMap;
+
+use super::database::{Database, InstalledRunner};
+use super::network::HttpClient;
+use super::compat_tool::{self, CompatToolInfo};
+use super::version::{Version, VersionFamily};
+
+/// Roots of the APIs release lists are fetched from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseApis {
+    /// GitHub REST API
+    pub github: String,
+    /// Forgejo API of dawn.wine, which hosts dwproton
+    pub dawn_wine: String,
+}
 
+impl Default for ReleaseApis {
+    fn default() -> Self {
+        Self {
+            github: "https://api.github.com".to_string(),
+            dawn_wine: "https://dawn.wine/api/v1".to_string(),
+        }
+    }
+}
+
+/// Cheap to clone; clones share the HTTP client and database. Every method
+/// takes `&self`, so a fetch can run on the tokio runtime without any lock.
+#[derive(Clone)]
 pub struct ToolManager {
-    tools_with_versions: Vec<ToolWithVersions>,
-    client: Client,
+    http: HttpClient,
     db: Database,
+    apis: ReleaseApis,
 }
 
 impl ToolManager {
     pub fn new() -> Self {
         let db = Database::new().expect("Failed to initialize database");
-        
-        Self { 
-            tools_with_versions: Vec::new(),
-            client: Client::builder()
-                .user_agent("ProtonUp-GTK/0.3.1")
-                .build()
-                .expect("Failed to create HTTP client"),
-            db,
-        }
+        Self::with_database(db)
     }
 
-    pub async fn fetch_tools_with_versions(&mut self) -> Result<Vec<ToolWithVersions>> {
-        let mut tools = Vec::new();
+    pub fn with_database(db: Database) -> Self {
+        Self::with_http_client(db, HttpClient::default())
+    }
 
-        // Fetch GE-Proton releases (last 4)
-        if let Ok(ge_proton_versions) = self.fetch_ge_proton_versions(4).await {
-            tools.push(ge_proton_versions);
-        }
+    /// Calls the release APIs with `http`, which may be shared with a [`super::Downloader`].
+    pub fn with_http_client(db: Database, http: HttpClient) -> Self {
+        Self { http, db, apis: ReleaseApis::default() }
+    }
 
-        // Fetch Wine-GE releases (last 4)
-        if let Ok(wine_ge_versions) = self.fetch_wine_ge_versions(4).await {
-            tools.push(wine_ge_versions);
-        }
+    /// Fetches release lists from `apis` instead, a local server in tests.
+    #[cfg(test)]
+    pub fn with_release_apis(mut self, apis: ReleaseApis) -> Self {
+        self.apis = apis;
+        self
+    }
 
-        // Fetch Spritz-Wine releases (last 4)
-        if let Ok(spritz_wine_versions) = self.fetch_spritz_wine_versions(4).await {
-            tools.push(spritz_wine_versions);
-        }
+    /// Fetches the last releases of every tool. Tools that fail to load are left
+    /// out; only if none loads is the first error returned.
+    pub async fn fetch_tools_with_versions(&self) -> Result<Vec<ToolWithVersions>> {
+        let results = [
+            self.fetch_ge_proton_versions(4).await,
+            self.fetch_wine_ge_versions(4).await,
+            self.fetch_spritz_wine_versions(4).await,
+            self.fetch_dwproton_versions(4).await,
+        ];
 
-        // Fetch dwproton releases (last 4)
-        if let Ok(dwproton_versions) = self.fetch_dwproton_versions(4).await {
-            tools.push(dwproton_versions);
+        let mut tools = Vec::new();
+        let mut first_error = None;
+        for result in results {
+            match result {
+                Ok(tool) => tools.push(tool),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
         }
 
-        self.tools_with_versions = tools;
-        Ok(self.tools_with_versions.clone())
+        match first_error {
+            Some(e) if tools.is_empty() => Err(e),
+            _ => Ok(tools),
+        }
     }
 
     async fn fetch_ge_proton_versions(&self, count: usize) -> Result<ToolWithVersions> {
         let url = format!(
-            "https://api.github.com/repos/GloriousEggroll/proton-ge-custom/releases?per_page={}",
-            count
+            "{}/repos/GloriousEggroll/proton-ge-custom/releases?per_page={}",
+            self.apis.github, count
         );
-        let releases: Vec<GitHubRelease> = self.client
-            .get(&url)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let response = self.http.client().get(&url).send().await?;
+        let releases: Vec<GitHubRelease> = check_response(response)?.json().await?;
 
+        let channel = self.get_release_channel("GE-Proton");
         let mut versions = Vec::new();
-        for release in releases {
+        for release in releases.into_iter().filter(|r| r.is_allowed(channel)) {
             if let Some(asset) = release.assets.iter().find(|a| a.name.ends_with(".tar.gz")) {
                 versions.push(ToolVersion {
                     version: release.tag_name.clone(),
                     download_url: asset.browser_download_url.clone(),
+                    size: asset.size,
+                    sha256: asset.sha256(),
+                    prerelease: release.prerelease,
                 });
             }
         }
+        sort_newest_first(&mut versions);
 
         Ok(ToolWithVersions {
             name: "GE-Proton".to_string(),
@@ -125,25 +266,26 @@ impl ToolManager {
 
     async fn fetch_wine_ge_versions(&self, count: usize) -> Result<ToolWithVersions> {
         let url = format!(
-            "https://api.github.com/repos/GloriousEggroll/wine-ge-custom/releases?per_page={}",
-            count
+            "{}/repos/GloriousEggroll/wine-ge-custom/releases?per_page={}",
+            self.apis.github, count
         );
-        let releases: Vec<GitHubRelease> = self.client
-            .get(&url)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let response = self.http.client().get(&url).send().await?;
+        let releases: Vec<GitHubRelease> = check_response(response)?.json().await?;
 
+        let channel = self.get_release_channel("Wine-GE");
         let mut versions = Vec::new();
-        for release in releases {
+        for release in releases.into_iter().filter(|r| r.is_allowed(channel)) {
             if let Some(asset) = release.assets.iter().find(|a| a.name.ends_with(".tar.xz")) {
                 versions.push(ToolVersion {
                     version: release.tag_name.clone(),
                     download_url: asset.browser_download_url.clone(),
+                    size: asset.size,
+                    sha256: asset.sha256(),
+                    prerelease: release.prerelease,
                 });
             }
         }
+        sort_newest_first(&mut versions);
 
         Ok(ToolWithVersions {
             name: "Wine-GE".to_string(),
@@ -155,27 +297,28 @@ impl ToolManager {
 
     async fn fetch_spritz_wine_versions(&self, count: usize) -> Result<ToolWithVersions> {
         let url = format!(
-            "https://api.github.com/repos/NelloKudo/Wine-Builds/releases?per_page={}",
-            count
+            "{}/repos/NelloKudo/Wine-Builds/releases?per_page={}",
+            self.apis.github, count
         );
-        let releases: Vec<GitHubRelease> = self.client
-            .get(&url)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let response = self.http.client().get(&url).send().await?;
+        let releases: Vec<GitHubRelease> = check_response(response)?.json().await?;
 
+        let channel = self.get_release_channel("Spritz-Wine");
         let mut versions = Vec::new();
-        for release in releases {
+        for release in releases.into_iter().filter(|r| r.is_allowed(channel)) {
             if let Some(asset) = release.assets.iter()
                 .find(|a| a.name.to_lowercase().contains("spritz") && a.name.ends_with(".tar.xz"))
             {
                 versions.push(ToolVersion {
                     version: release.tag_name.clone(),
                     download_url: asset.browser_download_url.clone(),
+                    size: asset.size,
+                    sha256: asset.sha256(),
+                    prerelease: release.prerelease,
                 });
             }
         }
+        sort_newest_first(&mut versions);
 
         Ok(ToolWithVersions {
             name: "Spritz-Wine".to_string(),
@@ -187,25 +330,26 @@ impl ToolManager {
 
     async fn fetch_dwproton_versions(&self, count: usize) -> Result<ToolWithVersions> {
         let url = format!(
-            "https://dawn.wine/api/v1/repos/dawn-winery/dwproton/releases?per_page={}",
-            count
+            "{}/repos/dawn-winery/dwproton/releases?per_page={}",
+            self.apis.dawn_wine, count
         );
-        let releases: Vec<GitHubRelease> = self.client
-            .get(&url)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let response = self.http.client().get(&url).send().await?;
+        let releases: Vec<GitHubRelease> = check_response(response)?.json().await?;
 
+        let channel = self.get_release_channel("dwproton");
         let mut versions = Vec::new();
-        for release in releases {
+        for release in releases.into_iter().filter(|r| r.is_allowed(channel)) {
             if let Some(asset) = release.assets.iter().find(|a| a.name.ends_with(".tar.xz")) {
                 versions.push(ToolVersion {
                     version: release.tag_name.clone(),
                     download_url: asset.browser_download_url.clone(),
+                    size: asset.size,
+                    sha256: asset.sha256(),
+                    prerelease: release.prerelease,
                 });
             }
         }
+        sort_newest_first(&mut versions);
 
         Ok(ToolWithVersions {
             name: "dwproton".to_string(),
@@ -215,75 +359,215 @@ impl ToolManager {
         })
     }
 
-    pub fn get_install_path(&self, launcher: &Launcher) -> Result<std::path::PathBuf> {
+    pub fn get_install_path(&self, launcher: &Launcher) -> Result<PathBuf> {
         // Check if there's a custom path set in the database
-        match launcher {
-            Launcher::Steam => {
-                if let Ok(Some(custom_path)) = self.db.get_steam_path() {
-                    return Ok(custom_path);
-                }
-            }
-            Launcher::Lutris => {
-                if let Ok(Some(custom_path)) = self.db.get_lutris_path() {
-                    return Ok(custom_path);
-                }
-            }
+        if let Ok(Some(custom_path)) = self.db.get_launcher_path(launcher) {
+            return Ok(custom_path);
         }
         
         // Use default paths
         let home_dir = dirs::home_dir()
-            .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
-        
-        let path = match launcher {
-            Launcher::Steam => {
-                home_dir.join(".steam/root/compatibilitytools.d")
-            }
-            Launcher::Lutris => {
-                home_dir.join(".local/share/lutris/runners/wine")
-            }
-        };
+            .ok_or_else(|| Error::Other("Could not determine home directory".to_string()))?;
         
-        Ok(path)
+        Ok(home_dir.join(launcher.default_relative_path()))
+    }
+
+    pub fn set_steam_path(&self, path: Option<PathBuf>) -> Result<()> {
+        self.db.set_steam_path(path.as_ref())
+    }
+
+    pub fn set_lutris_path(&self, path: Option<PathBuf>) -> Result<()> {
+        self.db.set_lutris_path(path.as_ref())
+    }
+
+    pub fn get_release_channel(&self, tool_name: &str) -> ReleaseChannel {
+        self.db.get_release_channel(tool_name).unwrap_or_default()
+    }
+
+    pub fn set_release_channel(&self, tool_name: &str, channel: ReleaseChannel) -> Result<()> {
+        self.db.set_release_channel(tool_name, channel)
     }
 
-    pub fn set_steam_path(&mut self, path: Option<std::path::PathBuf>) {
-        let _ = self.db.set_steam_path(path.as_ref());
+    /// Mirror URL templates for `tool_name`, tried before the release URL.
+    pub fn get_mirrors(&self, tool_name: &str) -> Vec<String> {
+        self.db.get_mirrors(tool_name).unwrap_or_default()
     }
 
-    pub fn set_lutris_path(&mut self, path: Option<std::path::PathBuf>) {
-        let _ = self.db.set_lutris_path(path.as_ref());
+    pub fn set_mirrors(&self, tool_name: &str, templates: &[String]) -> Result<()> {
+        self.db.set_mirrors(tool_name, templates)
     }
 
-    pub fn is_tool_installed(&self, tool_name: &str, launcher: &Launcher) -> bool {
-        // First check the database
-        if let Ok(true) = self.db.is_runner_installed(tool_name, launcher) {
-            return true;
+    /// The configured mirrors of `tool_name` expanded for one release asset.
+    pub fn mirror_urls(&self, tool_name: &str, version: &str, download_url: &str) -> Vec<String> {
+        self.get_mirrors(tool_name)
+            .iter()
+            .map(|template| expand_mirror_template(template, tool_name, version, download_url))
+            .collect()
+    }
+
+    /// Locates the directory a version is installed in, if any.
+    ///
+    /// The directory recorded at install time wins. Otherwise the install path is
+    /// scanned, see [`find_install_dir_in`].
+    pub fn find_install_dir(&self, version: &str, launcher: &Launcher) -> Option<PathBuf> {
+        let install_path = self.get_install_path(launcher).ok()?;
+        
+        let recorded = self.db.get_install_dirs(version, launcher).unwrap_or_default();
+        if let Some(dir) = pick_recorded_dir(recorded.iter(), &install_path) {
+            return Some(dir);
         }
         
-        // Fall back to filesystem check
-        if let Ok(install_path) = self.get_install_path(launcher) {
-            // Check if a directory with the tool name exists
-            // GE-Proton versions are typically named like "GE-Proton9-7"
-            // We need to check if any directory contains the tool name
-            if let Ok(entries) = std::fs::read_dir(&install_path) {
-                for entry in entries.flatten() {
-                    if let Ok(file_type) = entry.file_type() {
-                        if file_type.is_dir() {
-                            if let Some(dir_name) = entry.file_name().to_str() {
-                                // Check if directory name contains the tool version
-                                if dir_name == tool_name || dir_name.contains(tool_name) {
-                                    return true;
-                                }
-                            }
-                        }
-                    }
+        find_install_dir_in(&install_path, version)
+    }
+
+    /// Reads every launcher's install path and the recorded installs once, for
+    /// looking up many versions, e.g. when listing releases.
+    pub fn install_index(&self) -> InstallIndex {
+        let launchers = Launcher::ALL
+            .iter()
+            .filter_map(|launcher| {
+                let install_path = self.get_install_path(launcher).ok()?;
+                let dirs = InstallDirIndex::scan(&install_path);
+                Some((launcher.clone(), install_path, dirs))
+            })
+            .collect();
+        let mut recorded = self.db.list_installed_runners().unwrap_or_default();
+        recorded.sort_by_key(|runner| std::cmp::Reverse(runner.installed_at));
+        InstallIndex { launchers, recorded }
+    }
+
+    /// Metadata of every compatibility tool under the Steam install path.
+    pub fn installed_compat_tools(&self) -> Vec<CompatToolInfo> {
+        match self.get_install_path(&Launcher::Steam) {
+            Ok(path) => compat_tool::scan_compat_tools(&path),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Internal names that Steam will see more than once, with the directories declaring them.
+    pub fn duplicate_compat_tools(&self) -> Vec<(String, Vec<PathBuf>)> {
+        compat_tool::duplicate_internal_names(&self.installed_compat_tools())
+            .into_iter()
+            .collect()
+    }
+
+}
+
+/// Fills in a mirror URL template: `{tool}` becomes the tool name, `{version}`
+/// the release tag and `{file}` the asset file name of `download_url`, e.g.
+/// `https://mirror.example.com/{tool}/{version}/{file}`.
+pub fn expand_mirror_template(template: &str, tool_name: &str, version: &str, download_url: &str) -> String {
+    let file = download_url.rsplit('/').next().unwrap_or_default();
+    template
+        .replace("{tool}", tool_name)
+        .replace("{version}", version)
+        .replace("{file}", file)
+}
+
+/// The existing one of the directories a version was recorded at, most recent
+/// first. The same version may be recorded under several custom paths, so one
+/// under the current `install_path` is preferred.
+fn pick_recorded_dir<'a>(recorded: impl Iterator<Item = &'a PathBuf>, install_path: &Path) -> Option<PathBuf> {
+    let recorded: Vec<&PathBuf> = recorded.filter(|dir| dir.is_dir()).collect();
+    recorded.iter().find(|dir| dir.starts_with(install_path)).or(recorded.first()).map(|dir| dir.to_path_buf())
+}
+
+/// Where versions are installed in every launcher, as read by
+/// [`ToolManager::install_index`]. Does not see later changes.
+#[derive(Debug, Default)]
+pub struct InstallIndex {
+    launchers: Vec<(Launcher, PathBuf, InstallDirIndex)>,
+    /// Most recent install first
+    recorded: Vec<InstalledRunner>,
+}
+
+impl InstallIndex {
+    /// Same as [`ToolManager::find_install_dir`], from the snapshot.
+    pub fn find_install_dir(&self, version: &str, launcher: &Launcher) -> Option<PathBuf> {
+        let (_, install_path, dirs) = self.launchers.iter().find(|(known, _, _)| known == launcher)?;
+        let recorded = self.recorded
+            .iter()
+            .filter(|runner| runner.version == version && &runner.launcher == launcher)
+            .map(|runner| &runner.install_dir);
+        pick_recorded_dir(recorded, install_path).or_else(|| dirs.find(version))
+    }
+
+    pub fn is_installed(&self, version: &str, launcher: &Launcher) -> bool {
+        self.find_install_dir(version, launcher).is_some()
+    }
+
+    /// Display name from `compatibilitytool.vdf` of an installed Steam version.
+    pub fn steam_display_name(&self, version: &str) -> Option<String> {
+        let dir = self.find_install_dir(version, &Launcher::Steam)?;
+        let info = CompatToolInfo::read(&dir).ok()?;
+        info.display_name().map(|name| name.to_string())
+    }
+}
+
+/// The runner directories under one install path, read once and looked up by name.
+#[derive(Debug, Default)]
+pub struct InstallDirIndex {
+    /// Directory names, then the names declared in `compatibilitytool.vdf`
+    by_name: HashMap<String, PathBuf>,
+    /// Directories named like a release of a known [`VersionFamily`]
+    releases: Vec<(Version, PathBuf)>,
+}
+
+impl InstallDirIndex {
+    pub fn scan(install_path: &Path) -> Self {
+        let mut index = Self::default();
+        let Ok(entries) = std::fs::read_dir(install_path) else {
+            return index;
+        };
+        
+        let mut declared = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str() {
+                index.by_name.insert(name.to_string(), path.clone());
+                if let Some(version) = Version::parse(name).filter(|v| v.family != VersionFamily::Other) {
+                    index.releases.push((version, path.clone()));
                 }
             }
+            if let Ok(info) = CompatToolInfo::read(&path) {
+                declared.extend(info.names().map(|name| (name.to_string(), path.clone())));
+            }
+        }
+        // A directory named after a version wins over one declaring it
+        for (name, path) in declared {
+            index.by_name.entry(name).or_insert(path);
+        }
+        index
+    }
+
+    /// See [`find_install_dir_in`].
+    pub fn find(&self, version: &str) -> Option<PathBuf> {
+        if let Some(path) = self.by_name.get(version) {
+            return Some(path.clone());
         }
-        false
+        let wanted = Version::parse(version).filter(|v| v.family != VersionFamily::Other)?;
+        self.releases
+            .iter()
+            .find(|(found, _)| wanted.is_same_release(found))
+            .map(|(_, path)| path.clone())
     }
 }
 
+/// Finds the directory under `install_path` that holds exactly `version`.
+///
+/// Matches a directory named after the version, a tool whose
+/// `compatibilitytool.vdf` declares the version as internal or display name, or
+/// a directory whose name parses to the same release (e.g. the Lutris build
+/// `lutris-GE-Proton8-26-x86_64` for `GE-Proton8-26`). Only names of a known
+/// [`VersionFamily`] are compared by release; a bare "10.0" must match exactly.
+/// Substrings never match, so "GE-Proton9-1" is not found in "GE-Proton9-10".
+pub fn find_install_dir_in(install_path: &Path, version: &str) -> Option<PathBuf> {
+    InstallDirIndex::scan(install_path).find(version)
+}
+
 impl Default for ToolManager {
     fn default() -> Self {
         Self::new()
@@ -293,27 +577,239 @@ impl Default for ToolManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backend::test_util::{scratch_dir, FixtureServer};
+    use crate::backend::database::InstalledRunner;
+    use crate::backend::RunnerSource;
 
     #[tokio::test]
     async fn test_fetch_tools() {
-        let mut manager = ToolManager::new();
-        let result = manager.fetch_tools_with_versions().await;
-        
-        // We expect this to succeed or gracefully handle errors
-        match result {
-            Ok(tools) => {
-                // Should have at least one tool if API is available
-                if !tools.is_empty() {
-                    println!("Fetched {} tools", tools.len());
-                    for tool in tools {
-                        println!("- {}: {} versions", tool.name, tool.versions.len());
-                    }
-                }
-            }
-            Err(e) => {
-                println!("Error fetching tools (this may be expected if offline): {}", e);
-            }
+        let server = FixtureServer::start().await;
+        server.add("/repos/GloriousEggroll/proton-ge-custom/releases", r#"[
+            {"tag_name": "GE-Proton9-9", "assets": [
+                {"name": "GE-Proton9-9.tar.gz", "browser_download_url": "https://example.com/GE-Proton9-9.tar.gz", "size": 100}
+            ]},
+            {"tag_name": "GE-Proton10-1", "prerelease": true, "assets": [
+                {"name": "GE-Proton10-1.tar.gz", "browser_download_url": "https://example.com/GE-Proton10-1.tar.gz"}
+            ]},
+            {"tag_name": "GE-Proton9-10", "assets": [
+                {"name": "GE-Proton9-10.sha512sum", "browser_download_url": "https://example.com/GE-Proton9-10.sha512sum"},
+                {"name": "GE-Proton9-10.tar.gz", "browser_download_url": "https://example.com/GE-Proton9-10.tar.gz", "digest": "sha256:ab12"}
+            ]}
+        ]"#);
+        server.add("/repos/NelloKudo/Wine-Builds/releases", r#"[
+            {"tag_name": "10.0", "assets": [
+                {"name": "wine-vanilla-10.0.tar.xz", "browser_download_url": "https://example.com/wine-vanilla-10.0.tar.xz"},
+                {"name": "spritz-wine-tkg-10.0.tar.xz", "browser_download_url": "https://example.com/spritz-wine-tkg-10.0.tar.xz"}
+            ]}
+        ]"#);
+
+        // Wine-GE and dwproton are not served, the others still load
+        let root = scratch_dir("fetch-tools");
+        let db = Database::open(&root.join("settings.db")).unwrap();
+        let apis = ReleaseApis { github: server.url(""), dawn_wine: server.url("/api/v1") };
+        let manager = ToolManager::with_database(db.clone()).with_release_apis(apis);
+        let tools = manager.fetch_tools_with_versions().await.unwrap();
+        let names: Vec<_> = tools.iter().map(|tool| tool.name.as_str()).collect();
+        assert_eq!(names, vec!["GE-Proton", "Spritz-Wine"]);
+
+        let versions: Vec<_> = tools[0].versions.iter().map(|v| v.version.as_str()).collect();
+        assert_eq!(versions, vec!["GE-Proton9-10", "GE-Proton9-9"], "Newest first, without pre-releases");
+        assert_eq!(tools[0].versions[0].sha256.as_deref(), Some("ab12"));
+        assert_eq!(tools[0].versions[0].download_url, "https://example.com/GE-Proton9-10.tar.gz");
+        assert_eq!(tools[0].versions[1].size, Some(100));
+        assert_eq!(tools[1].versions[0].download_url, "https://example.com/spritz-wine-tkg-10.0.tar.xz");
+
+        manager.set_release_channel("GE-Proton", ReleaseChannel::PreRelease).unwrap();
+        let tools = manager.fetch_tools_with_versions().await.unwrap();
+        assert_eq!(tools[0].versions[0].version, "GE-Proton10-1");
+
+        // Only failing to load anything is an error
+        let unreachable = "http://127.0.0.1:1".to_string();
+        let offline = ToolManager::with_database(db)
+            .with_release_apis(ReleaseApis { github: unreachable.clone(), dawn_wine: unreachable });
+        assert!(offline.fetch_tools_with_versions().await.is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_handles_can_move_to_runtime() {
+        // Fetches and downloads are spawned on the tokio runtime with a clone of the handle
+        fn assert_shared<T: Clone + Send + Sync + 'static>() {}
+        assert_shared::<ToolManager>();
+        assert_shared::<crate::backend::Downloader>();
+    }
+
+    fn release(json: &str) -> GitHubRelease {
+        serde_json::from_str(json).expect("Failed to parse release")
+    }
+
+    #[test]
+    fn test_release_channel_filtering() {
+        let stable = release(r#"{"tag_name": "GE-Proton9-20", "assets": []}"#);
+        let pre = release(r#"{"tag_name": "GE-Proton10-rc1", "prerelease": true, "assets": []}"#);
+        let draft = release(r#"{"tag_name": "GE-Proton10-1", "draft": true, "assets": []}"#);
+
+        assert!(stable.is_allowed(ReleaseChannel::Stable));
+        assert!(stable.is_allowed(ReleaseChannel::PreRelease));
+        assert!(!pre.is_allowed(ReleaseChannel::Stable));
+        assert!(pre.is_allowed(ReleaseChannel::PreRelease));
+        assert!(!draft.is_allowed(ReleaseChannel::Stable));
+        assert!(!draft.is_allowed(ReleaseChannel::PreRelease));
+    }
+
+    #[test]
+    fn test_asset_digest() {
+        let release = release(r#"{"tag_name": "GE-Proton9-20", "assets": [
+            {"name": "GE-Proton9-20.tar.gz", "browser_download_url": "https://example.com/a", "digest": "sha256:AB12"},
+            {"name": "GE-Proton9-20.sha512sum", "browser_download_url": "https://example.com/b", "digest": null},
+            {"name": "old.tar.gz", "browser_download_url": "https://example.com/c"}
+        ]}"#);
+        let sums: Vec<_> = release.assets.iter().map(|asset| asset.sha256()).collect();
+        assert_eq!(sums, vec![Some("ab12".to_string()), None, None]);
+    }
+
+    #[test]
+    fn test_expand_mirror_template() {
+        let url = "https://github.com/GloriousEggroll/proton-ge-custom/releases/download/GE-Proton9-20/GE-Proton9-20.tar.gz";
+        assert_eq!(
+            expand_mirror_template("https://mirror.example.com/{tool}/{version}/{file}", "GE-Proton", "GE-Proton9-20", url),
+            "https://mirror.example.com/GE-Proton/GE-Proton9-20/GE-Proton9-20.tar.gz"
+        );
+        assert_eq!(expand_mirror_template("https://cdn.example.com/static.tar.gz", "GE-Proton", "GE-Proton9-20", url), "https://cdn.example.com/static.tar.gz");
+    }
+
+    #[test]
+    fn test_release_channel_round_trip() {
+        for channel in [ReleaseChannel::Stable, ReleaseChannel::PreRelease] {
+            assert_eq!(channel.as_str().parse::<ReleaseChannel>().ok(), Some(channel));
+        }
+        assert!("nightly".parse::<ReleaseChannel>().is_err());
+    }
+
+    #[test]
+    fn test_sort_newest_first() {
+        let mut versions: Vec<ToolVersion> = ["GE-Proton9-2", "nightly", "GE-Proton9-10", "GE-Proton9-9"]
+            .iter()
+            .map(|v| ToolVersion {
+                version: v.to_string(),
+                download_url: String::new(),
+                size: None,
+                sha256: None,
+                prerelease: false,
+            })
+            .collect();
+        sort_newest_first(&mut versions);
+        let order: Vec<_> = versions.iter().map(|v| v.version.as_str()).collect();
+        assert_eq!(order, vec!["GE-Proton9-10", "GE-Proton9-9", "GE-Proton9-2", "nightly"]);
+    }
+
+    fn write_compat_tool(dir: &Path, internal_name: &str, display_name: &str) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(
+            dir.join("compatibilitytool.vdf"),
+            format!(
+                "\"compatibilitytools\"\n{{\n  \"compat_tools\"\n  {{\n    \"{}\"\n    {{\n      \"install_path\" \".\"\n      \"display_name\" \"{}\"\n    }}\n  }}\n}}\n",
+                internal_name, display_name
+            ),
+        ).unwrap();
+    }
+
+    /// A compatibilitytools.d with an exact-name install, a renamed install and a Lutris build.
+    fn fake_compat_tree(name: &str) -> PathBuf {
+        let root = scratch_dir(name);
+        write_compat_tool(&root.join("GE-Proton9-10"), "GE-Proton9-10", "GE-Proton9-10");
+        write_compat_tool(&root.join("my-proton"), "GE-Proton9-5", "GE-Proton 9-5 (custom)");
+        std::fs::create_dir_all(root.join("lutris-GE-Proton8-26-x86_64")).unwrap();
+        std::fs::write(root.join("GE-Proton9-3.tar.gz"), b"").unwrap();
+        root
+    }
+
+    #[test]
+    fn test_find_install_dir_exact_name() {
+        let root = fake_compat_tree("exact-name");
+        assert_eq!(find_install_dir_in(&root, "GE-Proton9-10"), Some(root.join("GE-Proton9-10")));
+        // Substring of an installed version must not match
+        assert_eq!(find_install_dir_in(&root, "GE-Proton9-1"), None);
+        // Plain files are not installs
+        assert_eq!(find_install_dir_in(&root, "GE-Proton9-3"), None);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_find_install_dir_by_vdf_names() {
+        let root = fake_compat_tree("vdf-names");
+        assert_eq!(find_install_dir_in(&root, "GE-Proton9-5"), Some(root.join("my-proton")));
+        assert_eq!(find_install_dir_in(&root, "GE-Proton 9-5 (custom)"), Some(root.join("my-proton")));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_find_install_dir_by_release() {
+        let root = fake_compat_tree("release");
+        assert_eq!(
+            find_install_dir_in(&root, "GE-Proton8-26"),
+            Some(root.join("lutris-GE-Proton8-26-x86_64"))
+        );
+        assert_eq!(find_install_dir_in(&root, "GE-Proton8-2"), None);
+        assert_eq!(find_install_dir_in(&root.join("missing"), "GE-Proton9-10"), None);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_find_install_dir_ignores_unknown_families() {
+        let root = scratch_dir("unknown-family");
+        std::fs::create_dir_all(root.join("wine-10.0")).unwrap();
+        std::fs::create_dir_all(root.join("Proton 10.0")).unwrap();
+        // Numbers alone do not identify a release
+        assert_eq!(find_install_dir_in(&root, "10.0"), None);
+        assert_eq!(find_install_dir_in(&root, "10.0-0"), None);
+        assert_eq!(find_install_dir_in(&root, "Proton 10.0"), Some(root.join("Proton 10.0")));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_find_install_dir_prefers_recorded_dir() {
+        let root = fake_compat_tree("recorded");
+        let db = Database::open(&root.join("settings.db")).unwrap();
+        db.set_steam_path(Some(&root)).unwrap();
+        let manager = ToolManager::with_database(db);
+
+        // Recorded directory is used even though its name differs from the version
+        manager.db.add_installed_runner(&InstalledRunner::new("GE-Proton9-1", &Launcher::Steam, &root.join("GE-Proton9-10"), &RunnerSource::default())).unwrap();
+        assert_eq!(
+            manager.find_install_dir("GE-Proton9-1", &Launcher::Steam),
+            Some(root.join("GE-Proton9-10"))
+        );
+
+        // A stale record falls back to scanning the install path
+        manager.db.add_installed_runner(&InstalledRunner::new("GE-Proton9-5", &Launcher::Steam, &root.join("gone"), &RunnerSource::default())).unwrap();
+        assert_eq!(
+            manager.find_install_dir("GE-Proton9-5", &Launcher::Steam),
+            Some(root.join("my-proton"))
+        );
+        assert!(!manager.install_index().is_installed("GE-Proton9-7", &Launcher::Steam));
+
+        // Of two recorded copies, the one under the current install path wins
+        let other = scratch_dir("recorded-other");
+        std::fs::create_dir_all(other.join("GE-Proton9-1")).unwrap();
+        manager.db.add_installed_runner(&InstalledRunner::new("GE-Proton9-1", &Launcher::Steam, &other.join("GE-Proton9-1"), &RunnerSource::default())).unwrap();
+        assert_eq!(manager.db.get_install_dirs("GE-Proton9-1", &Launcher::Steam).unwrap().len(), 2);
+        assert_eq!(
+            manager.find_install_dir("GE-Proton9-1", &Launcher::Steam),
+            Some(root.join("GE-Proton9-10"))
+        );
+
+        // The snapshot answers the same as the live lookups
+        let index = manager.install_index();
+        for version in ["GE-Proton9-1", "GE-Proton9-5", "GE-Proton9-7", "GE-Proton9-10"] {
+            assert_eq!(
+                index.find_install_dir(version, &Launcher::Steam),
+                manager.find_install_dir(version, &Launcher::Steam),
+                "{}", version
+            );
         }
+        std::fs::remove_dir_all(&other).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
     }
 
     #[test]
@@ -0,0 +1,122 @@
+use std::path::Path;
+
+use super::compat_tool::CompatToolInfo;
+use super::Launcher;
+use super::error::{Error, Result};
+
+/// Checks that an extracted runner has the layout its launcher needs to run it.
+///
+/// The error names the first missing or broken piece, so it can be shown to the
+/// user as the reason the install was rolled back.
+pub fn validate_install(dir: &Path, launcher: &Launcher) -> Result<()> {
+    if !dir.is_dir() {
+        return Err(Error::Other(format!("{} is not a directory", dir.display())));
+    }
+
+    match launcher {
+        Launcher::Steam => validate_steam_tool(dir),
+        Launcher::Lutris | Launcher::Heroic | Launcher::Bottles => validate_wine_runner(dir),
+    }
+}
+
+fn validate_steam_tool(dir: &Path) -> Result<()> {
+    if !dir.join("proton").is_file() {
+        return Err(Error::Other("'proton' script is missing".to_string()));
+    }
+
+    if !dir.join("compatibilitytool.vdf").is_file() {
+        return Err(Error::Other("compatibilitytool.vdf is missing".to_string()));
+    }
+    let info = CompatToolInfo::read(dir)
+        .map_err(|e| Error::Other(format!("compatibilitytool.vdf is unreadable: {}", e)))?;
+    info.check_usable()?;
+
+    if !dir.join("files").is_dir() && !dir.join("dist").is_dir() {
+        return Err(Error::Other("Neither 'files/' nor 'dist/' directory is present".to_string()));
+    }
+
+    Ok(())
+}
+
+fn validate_wine_runner(dir: &Path) -> Result<()> {
+    let candidates = [dir.join("bin/wine"), dir.join("bin/wine64")];
+
+    if !candidates.iter().any(|path| path.is_file()) {
+        return Err(Error::Other("Neither 'bin/wine' nor 'bin/wine64' is present".to_string()));
+    }
+
+    if !candidates.iter().any(|path| is_executable(path)) {
+        return Err(Error::Other("'bin/wine' and 'bin/wine64' are not executable".to_string()));
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::test_util::{scratch_dir, write_steam_tool};
+
+    fn error_of(dir: &Path, launcher: &Launcher) -> String {
+        validate_install(dir, launcher).expect_err("Validation should fail").to_string()
+    }
+
+    #[test]
+    fn test_validate_steam_tool() {
+        let root = scratch_dir("validate-steam");
+        let tool = root.join("GE-Proton9-20");
+        write_steam_tool(&tool);
+        assert!(validate_install(&tool, &Launcher::Steam).is_ok());
+
+        std::fs::rename(tool.join("files"), tool.join("dist")).unwrap();
+        assert!(validate_install(&tool, &Launcher::Steam).is_ok());
+
+        std::fs::remove_dir_all(tool.join("dist")).unwrap();
+        assert!(error_of(&tool, &Launcher::Steam).contains("'files/' nor 'dist/'"));
+
+        std::fs::remove_file(tool.join("toolmanifest.vdf")).unwrap();
+        assert!(error_of(&tool, &Launcher::Steam).contains("toolmanifest.vdf"));
+
+        std::fs::remove_file(tool.join("compatibilitytool.vdf")).unwrap();
+        assert!(error_of(&tool, &Launcher::Steam).contains("compatibilitytool.vdf is missing"));
+
+        std::fs::remove_file(tool.join("proton")).unwrap();
+        assert!(error_of(&tool, &Launcher::Steam).contains("'proton'"));
+
+        assert!(error_of(&root.join("missing"), &Launcher::Steam).contains("not a directory"));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_validate_wine_runner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = scratch_dir("validate-wine");
+        let runner = root.join("lutris-GE-Proton8-26-x86_64");
+        std::fs::create_dir_all(runner.join("bin")).unwrap();
+        assert!(error_of(&runner, &Launcher::Lutris).contains("'bin/wine' nor 'bin/wine64'"));
+
+        let wine64 = runner.join("bin/wine64");
+        std::fs::write(&wine64, "").unwrap();
+        std::fs::set_permissions(&wine64, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(error_of(&runner, &Launcher::Lutris).contains("not executable"));
+
+        std::fs::set_permissions(&wine64, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(validate_install(&runner, &Launcher::Lutris).is_ok());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
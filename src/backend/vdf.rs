@@ -0,0 +1,200 @@
+use super::error::{Error, Result};
+
+/// A node of Valve's KeyValues text format, as used by `compatibilitytool.vdf`
+/// and `toolmanifest.vdf`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VdfValue {
+    String(String),
+    Object(Vec<(String, VdfValue)>),
+}
+
+impl VdfValue {
+    /// Looks up a direct child by key, case-insensitively like Steam does.
+    pub fn get(&self, key: &str) -> Option<&VdfValue> {
+        match self {
+            VdfValue::Object(entries) => entries
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v),
+            VdfValue::String(_) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            VdfValue::String(s) => Some(s),
+            VdfValue::Object(_) => None,
+        }
+    }
+
+    pub fn entries(&self) -> &[(String, VdfValue)] {
+        match self {
+            VdfValue::Object(entries) => entries,
+            VdfValue::String(_) => &[],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Str(String),
+    Open,
+    Close,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '/' => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    // Line comment
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                } else {
+                    return Err(Error::Other("Unexpected '/' in VDF".to_string()));
+                }
+            }
+            '[' => {
+                // Platform conditionals like [$WIN32] are ignored
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                }
+            }
+            '{' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            '}' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                let mut closed = false;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => {
+                            closed = true;
+                            break;
+                        }
+                        '\\' => match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some(other) => value.push(other),
+                            None => break,
+                        },
+                        other => value.push(other),
+                    }
+                }
+                if !closed {
+                    return Err(Error::Other("Unterminated string in VDF".to_string()));
+                }
+                tokens.push(Token::Str(value));
+            }
+            _ => {
+                let mut value = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+                        break;
+                    }
+                    value.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Str(value));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_entries<I>(tokens: &mut I, nested: bool) -> Result<Vec<(String, VdfValue)>>
+where
+    I: Iterator<Item = Token>,
+{
+    let mut entries = Vec::new();
+
+    loop {
+        let key = match tokens.next() {
+            Some(Token::Str(key)) => key,
+            Some(Token::Close) if nested => return Ok(entries),
+            None if !nested => return Ok(entries),
+            Some(Token::Close) => return Err(Error::Other("Unexpected '}' in VDF".to_string())),
+            Some(Token::Open) => return Err(Error::Other("Expected key, found '{' in VDF".to_string())),
+            None => return Err(Error::Other("Unexpected end of VDF, missing '}'".to_string())),
+        };
+
+        let value = match tokens.next() {
+            Some(Token::Str(value)) => VdfValue::String(value),
+            Some(Token::Open) => VdfValue::Object(parse_entries(tokens, true)?),
+            _ => return Err(Error::Other(format!("Missing value for key '{}' in VDF", key))),
+        };
+
+        entries.push((key, value));
+    }
+}
+
+/// Parses a KeyValues document into its top-level object.
+pub fn parse(input: &str) -> Result<VdfValue> {
+    let mut tokens = tokenize(input)?.into_iter();
+    Ok(VdfValue::Object(parse_entries(&mut tokens, false)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_compatibilitytool_vdf() {
+        let input = r#"
+"compatibilitytools"
+{
+  "compat_tools"
+  {
+    "GE-Proton9-20" // Internal name of this tool
+    {
+      "install_path" "."
+      "display_name" "GE-Proton9-20"
+      "from_oslist"  "windows"
+      "to_oslist"    "linux"
+    }
+  }
+}
+"#;
+        let root = parse(input).expect("Failed to parse");
+        let tools = root.get("compatibilitytools").and_then(|v| v.get("compat_tools")).unwrap();
+        let (name, tool) = &tools.entries()[0];
+        assert_eq!(name, "GE-Proton9-20");
+        assert_eq!(tool.get("display_name").and_then(|v| v.as_str()), Some("GE-Proton9-20"));
+        assert_eq!(tool.get("TO_OSLIST").and_then(|v| v.as_str()), Some("linux"));
+    }
+
+    #[test]
+    fn test_parse_unquoted_and_escapes() {
+        let root = parse("key value\n\"quoted\" \"a \\\"b\\\"\"\n[$WIN32] other {}").unwrap();
+        assert_eq!(root.get("key").and_then(|v| v.as_str()), Some("value"));
+        assert_eq!(root.get("quoted").and_then(|v| v.as_str()), Some("a \"b\""));
+        assert_eq!(root.get("other"), Some(&VdfValue::Object(Vec::new())));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("\"a\" {").is_err());
+        assert!(parse("\"a\" }").is_err());
+        assert!(parse("\"a\" \"unterminated").is_err());
+        assert!(parse("\"lonely\"").is_err());
+    }
+}
@@ -0,0 +1,267 @@
+use std::cmp::Ordering;
+
+/// Naming scheme a version string was recognised as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum VersionFamily {
+    /// `GE-ProtonX-Y`, used by GE-Proton and Wine-GE tags
+    GeProton,
+    /// `lutris-GE-ProtonX-Y-x86_64`, the Wine-GE archive and directory name
+    LutrisGeProton,
+    /// Names containing `spritz`, such as `spritz-wine-tkg-10.12-1`. Plain
+    /// release tags like `10.12-1` carry no family and parse as [`Self::Other`]
+    Spritz,
+    /// `dwproton-X.Y-Z`
+    DwProton,
+    /// Anything else that still contains a version number
+    Other,
+}
+
+impl VersionFamily {
+    /// Stable identifier used when persisting the family.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VersionFamily::GeProton => "ge-proton",
+            VersionFamily::LutrisGeProton => "lutris-ge-proton",
+            VersionFamily::Spritz => "spritz",
+            VersionFamily::DwProton => "dwproton",
+            VersionFamily::Other => "other",
+        }
+    }
+
+    /// Lutris builds of GE-Proton share their numbering with the Steam builds.
+    fn base(&self) -> VersionFamily {
+        match self {
+            VersionFamily::LutrisGeProton => VersionFamily::GeProton,
+            other => *other,
+        }
+    }
+}
+
+/// Parsed, comparable form of a tool version string.
+#[derive(Debug, Clone)]
+pub struct Version {
+    pub family: VersionFamily,
+    pub numbers: Vec<u32>,
+    /// Trailing non-numeric component such as `rc1` or `beta2`
+    pub pre_release: Option<String>,
+}
+
+impl Version {
+    pub fn parse(value: &str) -> Option<Self> {
+        let lower = value.to_lowercase();
+
+        let (family, rest) = if let Some(index) = lower.find("lutris-ge-proton") {
+            (VersionFamily::LutrisGeProton, &lower[index + "lutris-ge-proton".len()..])
+        } else if let Some(rest) = lower.strip_prefix("ge-proton") {
+            (VersionFamily::GeProton, rest)
+        } else if let Some(rest) = lower.strip_prefix("dwproton") {
+            (VersionFamily::DwProton, rest)
+        } else if lower.contains("spritz") {
+            (VersionFamily::Spritz, lower.as_str())
+        } else {
+            (VersionFamily::Other, lower.as_str())
+        };
+
+        let rest = rest
+            .trim_end_matches("-x86_64")
+            .trim_end_matches("-i686");
+
+        let mut numbers = Vec::new();
+        let mut pre_release = None;
+        let mut tokens = rest.split(['-', '.', '_']).filter(|t| !t.is_empty());
+
+        for token in tokens.by_ref() {
+            if let Ok(number) = token.parse::<u32>() {
+                numbers.push(number);
+            } else if !numbers.is_empty() {
+                pre_release = Some(token.to_string());
+                break;
+            }
+            // Words before the first number (e.g. "wine-tkg") are part of the name
+        }
+
+        if let Some(tag) = pre_release.as_mut() {
+            for token in tokens {
+                tag.push('-');
+                tag.push_str(token);
+            }
+        }
+
+        if numbers.is_empty() {
+            return None;
+        }
+
+        Some(Self { family, numbers, pre_release })
+    }
+
+    /// True if both strings name the same release, ignoring the Lutris packaging prefix.
+    pub fn is_same_release(&self, other: &Version) -> bool {
+        self.family.base() == other.family.base() && self.cmp_release(other) == Ordering::Equal
+    }
+
+    /// True if `self` is a later release of the same family than `other`.
+    pub fn is_newer_than(&self, other: &Version) -> bool {
+        self.family.base() == other.family.base() && self.cmp_release(other) == Ordering::Greater
+    }
+
+    fn cmp_release(&self, other: &Version) -> Ordering {
+        let len = self.numbers.len().max(other.numbers.len());
+        for i in 0..len {
+            let a = self.numbers.get(i).copied().unwrap_or(0);
+            let b = other.numbers.get(i).copied().unwrap_or(0);
+            match a.cmp(&b) {
+                Ordering::Equal => continue,
+                ordering => return ordering,
+            }
+        }
+
+        // A final release sorts after any of its pre-releases
+        match (&self.pre_release, &other.pre_release) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => cmp_pre_release(a, b),
+        }
+    }
+}
+
+/// Orders tags like `rc2` before `rc10` by comparing the numeric suffix as a number.
+fn cmp_pre_release(a: &str, b: &str) -> Ordering {
+    fn split(tag: &str) -> (&str, Option<u32>) {
+        let digits = tag.len() - tag.trim_start_matches(|c: char| !c.is_ascii_digit()).len();
+        let (word, number) = tag.split_at(digits);
+        (word, number.parse().ok())
+    }
+
+    let (word_a, number_a) = split(a);
+    let (word_b, number_b) = split(b);
+    word_a.cmp(word_b)
+        .then(number_a.cmp(&number_b))
+        .then(a.cmp(b))
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.family.base().cmp(&other.family.base())
+            .then_with(|| self.cmp_release(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(value: &str) -> Version {
+        Version::parse(value).unwrap_or_else(|| panic!("Failed to parse {}", value))
+    }
+
+    #[test]
+    fn test_parse_ge_proton() {
+        let version = parse("GE-Proton9-20");
+        assert_eq!(version.family, VersionFamily::GeProton);
+        assert_eq!(version.numbers, vec![9, 20]);
+        assert_eq!(version.pre_release, None);
+
+        let rc = parse("GE-Proton10-rc1");
+        assert_eq!(rc.numbers, vec![10]);
+        assert_eq!(rc.pre_release.as_deref(), Some("rc1"));
+    }
+
+    #[test]
+    fn test_parse_lutris_ge_proton() {
+        let version = parse("lutris-GE-Proton8-26-x86_64");
+        assert_eq!(version.family, VersionFamily::LutrisGeProton);
+        assert_eq!(version.numbers, vec![8, 26]);
+        assert_eq!(version.pre_release, None);
+
+        let archive = parse("wine-lutris-GE-Proton8-26-x86_64");
+        assert_eq!(archive.family, VersionFamily::LutrisGeProton);
+        assert_eq!(archive.numbers, vec![8, 26]);
+    }
+
+    #[test]
+    fn test_parse_spritz() {
+        let version = parse("spritz-wine-tkg-10.12-1");
+        assert_eq!(version.family, VersionFamily::Spritz);
+        assert_eq!(version.numbers, vec![10, 12, 1]);
+
+        let plain = parse("10.12");
+        assert_eq!(plain.family, VersionFamily::Other);
+        assert_eq!(plain.numbers, vec![10, 12]);
+    }
+
+    #[test]
+    fn test_parse_dwproton() {
+        let version = parse("dwproton-10.0-3");
+        assert_eq!(version.family, VersionFamily::DwProton);
+        assert_eq!(version.numbers, vec![10, 0, 3]);
+
+        let beta = parse("dwproton-10.0-beta2");
+        assert_eq!(beta.numbers, vec![10, 0]);
+        assert_eq!(beta.pre_release.as_deref(), Some("beta2"));
+    }
+
+    #[test]
+    fn test_parse_rejects_versionless_names() {
+        assert!(Version::parse("GE-Proton").is_none());
+        assert!(Version::parse("latest").is_none());
+        assert!(Version::parse("").is_none());
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut versions = [
+            parse("GE-Proton9-1"),
+            parse("GE-Proton9-10"),
+            parse("GE-Proton10-rc2"),
+            parse("GE-Proton9-2"),
+            parse("GE-Proton10-1"),
+            parse("GE-Proton10-rc10"),
+        ];
+        versions.sort();
+        let numbers: Vec<_> = versions.iter()
+            .map(|v| (v.numbers.clone(), v.pre_release.clone()))
+            .collect();
+        assert_eq!(numbers, vec![
+            (vec![9, 1], None),
+            (vec![9, 2], None),
+            (vec![9, 10], None),
+            (vec![10], Some("rc2".to_string())),
+            (vec![10], Some("rc10".to_string())),
+            (vec![10, 1], None),
+        ]);
+    }
+
+    #[test]
+    fn test_is_newer_than() {
+        assert!(parse("GE-Proton9-10").is_newer_than(&parse("GE-Proton9-9")));
+        assert!(!parse("GE-Proton9-9").is_newer_than(&parse("GE-Proton9-10")));
+        assert!(parse("dwproton-10.0-3").is_newer_than(&parse("dwproton-9.5-7")));
+        assert!(parse("spritz-wine-tkg-10.12").is_newer_than(&parse("spritz-wine-tkg-10.9-2")));
+        // Different families are never comparable as "newer"
+        assert!(!parse("GE-Proton99-1").is_newer_than(&parse("dwproton-1.0-1")));
+    }
+
+    #[test]
+    fn test_exact_matching() {
+        let installed = parse("GE-Proton9-10");
+        assert!(!installed.is_same_release(&parse("GE-Proton9-1")));
+        assert!(installed.is_same_release(&parse("GE-Proton9-10")));
+        assert!(parse("lutris-GE-Proton8-26-x86_64").is_same_release(&parse("GE-Proton8-26")));
+        assert!(parse("dwproton-10.0").is_same_release(&parse("dwproton-10.0-0")));
+    }
+}
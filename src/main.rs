@@ -11,8 +11,31 @@ use application::Application;
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(feature = "gui")]
     {
+        // Data location and speed limit flags are ours, everything else goes to GTK
+        let parsed = backend::paths::parse_args(std::env::args_os().collect())
+            .and_then(|(location, args)| {
+                let (rate_limit, args) = backend::network::parse_rate_limit_arg(args)?;
+                Ok((location, rate_limit, args))
+            });
+        let args = match parsed {
+            Ok((location, rate_limit, args)) => {
+                if let Some(location) = location {
+                    backend::paths::init(location);
+                }
+                if let Some(rate_limit) = rate_limit {
+                    backend::network::init_rate_limit_arg(rate_limit);
+                }
+                // GTK only takes UTF-8 arguments
+                args.iter().map(|arg| arg.to_string_lossy().into_owned()).collect::<Vec<_>>()
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+        };
+        
         let app = Application::new();
-        let exit_code = app.run();
+        let exit_code = app.run(&args);
         std::process::exit(exit_code);
     }
     
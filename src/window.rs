@@ -2,18 +2,34 @@ use gtk::prelude::*;
 use libadwaita as adw;
 use adw::prelude::*;
 use gtk::{Button, Box, Orientation, Label, ScrolledWindow};
-use std::sync::{Arc, Mutex};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
 
-use crate::backend::{ToolManager, Downloader};
+use crate::backend::{ToolManager, ToolWithVersions, Downloader, Database, Error, Launcher, RunnerSource};
+use crate::backend::disk_space::check_space;
+use crate::backend::downloader::DownloadProgress;
+use crate::backend::network::HttpClient;
+use crate::backend::tool_manager::InstallIndex;
+use crate::backend::installer::{check_version_name, install_to_targets, space_requirements, uninstall, InstallSource};
+use crate::backend::manifest::{apply_plan, RunnerManifest, SyncPlan, SyncReport};
+
+/// What a running install reports to the row that started it.
+enum InstallUpdate {
+    Download(DownloadProgress),
+    Status(String),
+}
 
 pub struct MainWindow {
     window: adw::ApplicationWindow,
-    tool_manager: Arc<Mutex<ToolManager>>,
-    downloader: Arc<Mutex<Downloader>>,
+    tool_manager: ToolManager,
+    downloader: Downloader,
+    db: Database,
     toast_overlay: adw::ToastOverlay,
     runtime_handle: Arc<tokio::runtime::Handle>,
     list_group: adw::PreferencesGroup,
-    expander_rows: Arc<Mutex<Vec<adw::ExpanderRow>>>,
+    refresh_button: Button,
+    expander_rows: Rc<RefCell<Vec<adw::ExpanderRow>>>,
 }
 
 impl MainWindow {
@@ -25,12 +41,46 @@ impl MainWindow {
             .default_height(600)
             .build();
 
-        let tool_manager = Arc::new(Mutex::new(ToolManager::new()));
-        let downloader = Arc::new(Mutex::new(Downloader::new()));
-
         // Create toast overlay for notifications
         let toast_overlay = adw::ToastOverlay::new();
 
+        // One settings database shared by everything in the window. If it cannot be
+        // opened, keep working on an in-memory copy and tell the user.
+        let db = Database::new().unwrap_or_else(|e| {
+            let toast = adw::Toast::new(&format!("Settings could not be loaded, changes will not be saved: {}", e));
+            toast.set_timeout(0);
+            toast_overlay.add_toast(toast);
+            Database::open(std::path::Path::new(":memory:")).expect("Failed to create in-memory database")
+        });
+
+        // API calls and downloads share one client, so they use the same proxy and certificates
+        let http_client = HttpClient::default();
+        let tool_manager = ToolManager::with_http_client(db.clone(), http_client.clone());
+        let downloader = Downloader::with_http_client(http_client);
+        
+        // Apply the saved archive cache settings
+        let enabled = db.get_archive_cache_enabled().unwrap_or(true);
+        let limit_mb = db.get_archive_cache_limit_mb()
+            .unwrap_or(crate::backend::database::DEFAULT_ARCHIVE_CACHE_LIMIT_MB);
+        if let Err(e) = downloader.configure_cache(enabled, limit_mb * 1024 * 1024) {
+            let toast = adw::Toast::new(&format!("Download cache unavailable: {}", e));
+            toast.set_timeout(5);
+            toast_overlay.add_toast(toast);
+        }
+        downloader.set_temp_dir(db.get_temp_dir().ok().flatten());
+        downloader.set_streaming(db.get_streaming_extraction().unwrap_or(false));
+        downloader.set_probe_mirrors(db.get_probe_mirrors().unwrap_or(false));
+        if let Err(e) = downloader.configure_network(db.get_network_settings().unwrap_or_default()) {
+            let toast = adw::Toast::new(&format!("Network settings not applied: {}", e));
+            toast.set_timeout(5);
+            toast_overlay.add_toast(toast);
+        }
+        // A limit given on the command line applies to this session only
+        let rate_limit_kib = crate::backend::network::rate_limit_arg()
+            .or_else(|| db.get_download_rate_limit_kib().ok().flatten())
+            .filter(|limit| *limit > 0);
+        downloader.set_rate_limit(rate_limit_kib.map(|limit| limit * 1024));
+
         let header_bar = adw::HeaderBar::builder().build();
         
         // Add menu button for settings
@@ -79,10 +129,11 @@ impl MainWindow {
         let tool_manager_refresh = tool_manager.clone();
         let list_group_refresh = list_group.clone();
         let downloader_refresh = downloader.clone();
+        let db_refresh = db.clone();
         let runtime_handle_refresh = runtime_handle.clone();
         
         // Store references to added expander rows so we can remove them on refresh
-        let expander_rows: Arc<Mutex<Vec<adw::ExpanderRow>>> = Arc::new(Mutex::new(Vec::new()));
+        let expander_rows: Rc<RefCell<Vec<adw::ExpanderRow>>> = Rc::new(RefCell::new(Vec::new()));
         let expander_rows_refresh = expander_rows.clone();
         
         refresh_button.connect_clicked(move |btn| {
@@ -92,17 +143,12 @@ impl MainWindow {
             let list_group = list_group_refresh.clone();
             let button = btn.clone();
             let downloader = downloader_refresh.clone();
+            let db = db_refresh.clone();
             let runtime_handle = runtime_handle_refresh.clone();
             let expander_rows = expander_rows_refresh.clone();
             
             glib::MainContext::default().spawn_local(async move {
-                // Enter the Tokio runtime context for the async operations
-                let _guard = runtime_handle.enter();
-                
-                let result = tool_manager.lock()
-                    .expect("Failed to lock tool manager")
-                    .fetch_tools_with_versions()
-                    .await;
+                let result = Self::fetch_tools(&tool_manager, &runtime_handle).await;
                 
                 button.set_sensitive(true);
                 
@@ -110,36 +156,37 @@ impl MainWindow {
                     Ok(tools) => {
                         // Clear existing rows that we previously added
                         {
-                            let mut rows = expander_rows.lock().expect("Failed to lock expander rows");
+                            let mut rows = expander_rows.borrow_mut();
                             for row in rows.drain(..) {
                                 list_group.remove(&row);
                             }
                         }
                         
-                        // Add new rows with versions
+                        // Add new rows with versions, reading the install dirs once for all of them
+                        let installed = Rc::new(RefCell::new(tool_manager.install_index()));
                         for tool in &tools {
                             let expander = Self::add_tool_with_versions(
                                 &list_group,
                                 tool,
                                 tool_manager.clone(),
+                                installed.clone(),
                                 downloader.clone(),
+                                db.clone(),
                                 toast_overlay.clone(),
                                 runtime_handle.clone(),
                             );
                             // Store the expander so we can remove it next time
-                            expander_rows.lock().expect("Failed to lock expander rows").push(expander);
+                            expander_rows.borrow_mut().push(expander);
                         }
                         
                         let msg = format!("Loaded {} compatibility tools", tools.len());
                         let toast = adw::Toast::new(&msg);
                         toast.set_timeout(3);
                         toast_overlay.add_toast(toast);
+                        Self::warn_duplicate_compat_tools(&tool_manager, &toast_overlay);
                     }
                     Err(e) => {
-                        let error_msg = format!("Failed to refresh: {}", e);
-                        let toast = adw::Toast::new(&error_msg);
-                        toast.set_timeout(5);
-                        toast_overlay.add_toast(toast);
+                        Self::error_toast(&toast_overlay, "Failed to refresh", &e, move || button.emit_clicked());
                     }
                 }
             });
@@ -159,82 +206,139 @@ impl MainWindow {
         window.set_content(Some(&toast_overlay));
 
         // Setup menu
-        Self::setup_menu(&menu_button, &window, &toast_overlay, tool_manager.clone());
+        Self::setup_menu(&menu_button, &window, &toast_overlay, tool_manager.clone(), downloader.clone(), db.clone(), runtime_handle.clone());
 
         let main_window = Self { 
             window,
             tool_manager,
             downloader,
+            db,
             toast_overlay,
             runtime_handle,
             list_group,
+            refresh_button,
             expander_rows,
         };
         
         main_window
     }
 
+    /// Fetches the tool list on the tokio runtime, leaving the main loop free.
+    async fn fetch_tools(
+        tool_manager: &ToolManager,
+        runtime_handle: &tokio::runtime::Handle,
+    ) -> crate::backend::Result<Vec<ToolWithVersions>> {
+        let tool_manager = tool_manager.clone();
+        runtime_handle
+            .spawn(async move { tool_manager.fetch_tools_with_versions().await })
+            .await?
+    }
+
+    /// Starts `work` on the Tokio runtime right away, to be awaited from the
+    /// GLib loop. Tasks there can overlap, so none of them may enter the
+    /// runtime context themselves.
+    fn run_on_runtime<T, W>(
+        runtime_handle: &tokio::runtime::Handle,
+        work: W,
+    ) -> impl std::future::Future<Output = crate::backend::Result<T>>
+    where
+        T: Send + 'static,
+        W: std::future::Future<Output = crate::backend::Result<T>> + Send + 'static,
+    {
+        let task = runtime_handle.spawn(work);
+        async move { task.await? }
+    }
+
     fn refresh_tools_list(&self) {
         let toast_overlay = self.toast_overlay.clone();
         let tool_manager = self.tool_manager.clone();
         let list_group = self.list_group.clone();
         let downloader = self.downloader.clone();
+        let db = self.db.clone();
         let runtime_handle = self.runtime_handle.clone();
         let expander_rows = self.expander_rows.clone();
+        let refresh_button = self.refresh_button.clone();
         
         glib::MainContext::default().spawn_local(async move {
-            // Enter the Tokio runtime context for the async operations
-            let _guard = runtime_handle.enter();
-            
-            let result = tool_manager.lock()
-                .expect("Failed to lock tool manager")
-                .fetch_tools_with_versions()
-                .await;
+            let result = Self::fetch_tools(&tool_manager, &runtime_handle).await;
             
             match result {
                 Ok(tools) => {
                     // Clear existing rows that we previously added
                     {
-                        let mut rows = expander_rows.lock().expect("Failed to lock expander rows");
+                        let mut rows = expander_rows.borrow_mut();
                         for row in rows.drain(..) {
                             list_group.remove(&row);
                         }
                     }
                     
-                    // Add new rows with versions
+                    // Add new rows with versions, reading the install dirs once for all of them
+                    let installed = Rc::new(RefCell::new(tool_manager.install_index()));
                     for tool in &tools {
                         let expander = Self::add_tool_with_versions(
                             &list_group,
                             &tool,
                             tool_manager.clone(),
+                            installed.clone(),
                             downloader.clone(),
+                            db.clone(),
                             toast_overlay.clone(),
                             runtime_handle.clone(),
                         );
                         // Store the expander so we can remove it next time
-                        expander_rows.lock().expect("Failed to lock expander rows").push(expander);
+                        expander_rows.borrow_mut().push(expander);
                     }
                     
                     let msg = format!("Loaded {} compatibility tools", tools.len());
                     let toast = adw::Toast::new(&msg);
                     toast.set_timeout(3);
                     toast_overlay.add_toast(toast);
+                    Self::warn_duplicate_compat_tools(&tool_manager, &toast_overlay);
                 }
                 Err(e) => {
-                    let error_msg = format!("Failed to refresh: {}", e);
-                    let toast = adw::Toast::new(&error_msg);
-                    toast.set_timeout(5);
-                    toast_overlay.add_toast(toast);
+                    Self::error_toast(&toast_overlay, "Failed to refresh", &e, move || refresh_button.emit_clicked());
                 }
             }
         });
     }
 
+    /// Shows a failed action with what the user can do about it. Errors that may
+    /// go away on their own get a "Retry" button running `retry`.
+    fn error_toast(toast_overlay: &adw::ToastOverlay, action: &str, error: &Error, retry: impl Fn() + 'static) {
+        let toast = adw::Toast::new(&format!("{}: {}", action, error.user_message()));
+        toast.set_timeout(5);
+        if error.is_retryable() {
+            toast.set_button_label(Some("Retry"));
+            toast.connect_button_clicked(move |_| retry());
+        }
+        toast_overlay.add_toast(toast);
+    }
+
+    fn warn_duplicate_compat_tools(tool_manager: &ToolManager, toast_overlay: &adw::ToastOverlay) {
+        let duplicates = tool_manager.duplicate_compat_tools();
+        
+        // Steam loads only one tool per internal name and ignores the others
+        for (internal_name, dirs) in duplicates {
+            let msg = format!(
+                "{} is installed {} times for Steam, only one will be used",
+                internal_name,
+                dirs.len()
+            );
+            let toast = adw::Toast::new(&msg);
+            toast.set_timeout(5);
+            toast_overlay.add_toast(toast);
+        }
+    }
+
+    /// `installed` is shared by every row of one refresh and is re-read after an install or delete.
+    #[allow(clippy::too_many_arguments)]
     fn add_tool_with_versions(
         list_group: &adw::PreferencesGroup,
         tool: &crate::backend::ToolWithVersions,
-        tool_manager: Arc<Mutex<ToolManager>>,
-        downloader: Arc<Mutex<Downloader>>,
+        tool_manager: ToolManager,
+        installed: Rc<RefCell<InstallIndex>>,
+        downloader: Downloader,
+        db: Database,
         toast_overlay: adw::ToastOverlay,
         runtime_handle: Arc<tokio::runtime::Handle>,
     ) -> adw::ExpanderRow {
@@ -246,99 +350,128 @@ impl MainWindow {
         
         // Remove the fixed launcher badge - users will now choose per installation
         
+        // Newest version installed for either launcher, used for the update badge
+        let mut newest_installed: Option<crate::backend::version::Version> = None;
+        
         // Add version rows
         for version in &tool.versions {
             let version_row = adw::ActionRow::builder()
                 .title(&version.version)
                 .build();
             
-            // Create launcher selector dropdown
-            let launcher_model = gtk::StringList::new(&["Steam", "Lutris"]);
-            let launcher_dropdown = gtk::DropDown::builder()
-                .model(&launcher_model)
-                .selected(if tool.default_launcher == crate::backend::Launcher::Steam { 0 } else { 1 })
+            // Launcher selector with one check button per launcher, so a single
+            // download can be installed into several launchers at once
+            let launcher_checks: Vec<(Launcher, gtk::CheckButton)> = Launcher::ALL.iter()
+                .map(|launcher| {
+                    let check = gtk::CheckButton::builder()
+                        .label(launcher.to_string())
+                        .active(*launcher == tool.default_launcher)
+                        .build();
+                    (launcher.clone(), check)
+                })
+                .collect();
+            
+            let launcher_box = Box::new(Orientation::Vertical, 6);
+            for (_, check) in &launcher_checks {
+                launcher_box.append(check);
+            }
+            let launcher_popover = gtk::Popover::builder()
+                .child(&launcher_box)
+                .build();
+            let launcher_button = gtk::MenuButton::builder()
+                .label(Self::launchers_label(&Self::selected_launchers(&launcher_checks)))
+                .popover(&launcher_popover)
                 .valign(gtk::Align::Center)
                 .build();
             
-            // Check installation status for both launchers
-            let is_installed_steam = tool_manager.lock()
-                .expect("Failed to lock tool manager")
-                .is_tool_installed(&version.version, &crate::backend::Launcher::Steam);
-            let is_installed_lutris = tool_manager.lock()
-                .expect("Failed to lock tool manager")
-                .is_tool_installed(&version.version, &crate::backend::Launcher::Lutris);
+            // Check installation status for every launcher
+            let installed_launchers: Vec<Launcher> = Launcher::ALL.iter()
+                .filter(|launcher| installed.borrow().is_installed(&version.version, launcher))
+                .cloned()
+                .collect();
             
-            // Determine initial button state based on selected launcher
-            let initial_selected = launcher_dropdown.selected();
-            let is_installed = if initial_selected == 0 { is_installed_steam } else { is_installed_lutris };
+            if !installed_launchers.is_empty() {
+                if let Some(parsed) = version.parsed() {
+                    if newest_installed.as_ref().is_none_or(|newest| parsed.is_newer_than(newest)) {
+                        newest_installed = Some(parsed);
+                    }
+                }
+            }
             
+            // Show the name Steam lists the tool under when it differs from the tag
+            if installed_launchers.contains(&Launcher::Steam) {
+                let display_name = installed.borrow().steam_display_name(&version.version);
+                if let Some(display_name) = display_name.filter(|name| name != &version.version) {
+                    version_row.set_subtitle(&display_name);
+                }
+            }
+            
+            // Determine initial button state based on selected launchers
             let action_button = Button::builder()
-                .label(if is_installed { "Delete" } else { "Install" })
                 .valign(gtk::Align::Center)
                 .build();
+            Self::update_action_button(
+                &action_button,
+                &installed.borrow(),
+                &version.version,
+                &Self::selected_launchers(&launcher_checks),
+            );
             
-            if is_installed {
-                action_button.add_css_class("destructive-action");
-            } else {
-                action_button.add_css_class("suggested-action");
+            // Update selector label and button when launcher selection changes
+            for (_, check) in &launcher_checks {
+                let launcher_checks = launcher_checks.clone();
+                let launcher_button = launcher_button.clone();
+                let action_button = action_button.clone();
+                let version = version.version.clone();
+                let installed = installed.clone();
+                check.connect_toggled(move |_| {
+                    let selected = Self::selected_launchers(&launcher_checks);
+                    launcher_button.set_label(&Self::launchers_label(&selected));
+                    Self::update_action_button(&action_button, &installed.borrow(), &version, &selected);
+                });
             }
             
-            // Update button when launcher selection changes
-            let action_button_for_dropdown = action_button.clone();
-            let version_for_dropdown = version.version.clone();
-            let tool_manager_for_dropdown = tool_manager.clone();
-            launcher_dropdown.connect_selected_notify(move |dropdown| {
-                let selected = dropdown.selected();
-                let launcher = if selected == 0 {
-                    crate::backend::Launcher::Steam
-                } else {
-                    crate::backend::Launcher::Lutris
-                };
-                
-                let is_installed = tool_manager_for_dropdown.lock()
-                    .expect("Failed to lock tool manager")
-                    .is_tool_installed(&version_for_dropdown, &launcher);
-                
-                action_button_for_dropdown.remove_css_class("suggested-action");
-                action_button_for_dropdown.remove_css_class("destructive-action");
-                
-                if is_installed {
-                    action_button_for_dropdown.set_label("Delete");
-                    action_button_for_dropdown.add_css_class("destructive-action");
-                } else {
-                    action_button_for_dropdown.set_label("Install");
-                    action_button_for_dropdown.add_css_class("suggested-action");
-                }
-            });
+            // Download details, shown only while downloading
+            let progress_bar = gtk::ProgressBar::builder()
+                .show_text(true)
+                .valign(gtk::Align::Center)
+                .visible(false)
+                .build();
+            progress_bar.add_css_class("caption");
             
             // Clone for closure
             let download_url = version.download_url.clone();
+            let archive_size = version.size;
+            let archive_sha256 = version.sha256.clone();
             let version_str = version.version.clone();
             let tool_name = tool.name.clone();
-            let launcher_dropdown_for_button = launcher_dropdown.clone();
+            let launcher_checks_for_button = launcher_checks.clone();
             let tool_manager_clone = tool_manager.clone();
+            let installed_clone = installed.clone();
             let downloader_clone = downloader.clone();
+            let db_clone = db.clone();
             let toast_overlay_clone = toast_overlay.clone();
             let button_clone = action_button.clone();
+            let progress_bar_clone = progress_bar.clone();
             let runtime_handle_clone = runtime_handle.clone();
             
             action_button.connect_clicked(move |_| {
                 let download_url = download_url.clone();
+                let archive_sha256 = archive_sha256.clone();
                 let version = version_str.clone();
                 let tool_name = tool_name.clone();
+                let launcher_checks = launcher_checks_for_button.clone();
                 
-                // Get selected launcher from dropdown
-                let selected = launcher_dropdown_for_button.selected();
-                let launcher = if selected == 0 {
-                    crate::backend::Launcher::Steam
-                } else {
-                    crate::backend::Launcher::Lutris
-                };
+                // Get selected launchers from the selector
+                let launchers = Self::selected_launchers(&launcher_checks);
                 
                 let tool_manager = tool_manager_clone.clone();
+                let installed = installed_clone.clone();
                 let downloader = downloader_clone.clone();
+                let db = db_clone.clone();
                 let toast_overlay = toast_overlay_clone.clone();
                 let button = button_clone.clone();
+                let progress_bar = progress_bar_clone.clone();
                 let runtime_handle = runtime_handle_clone.clone();
                 
                 // Check if we're deleting or installing
@@ -352,192 +485,435 @@ impl MainWindow {
                     button.set_label("Deleting...");
                     
                     glib::MainContext::default().spawn_local(async move {
-                        // Enter the Tokio runtime context for async operations
-                        let _guard = runtime_handle.enter();
+                        let result = {
+                            let version = version.clone();
+                            let tool_manager = tool_manager.clone();
+                            Self::run_on_runtime(&runtime_handle, async move {
+                                Self::delete_tool_version(&version, &launchers, tool_manager, &db).await
+                            }).await
+                        };
                         
-                        let result = Self::delete_tool_version(
+                        *installed.borrow_mut() = tool_manager.install_index();
+                        Self::update_action_button(
+                            &button,
+                            &installed.borrow(),
                             &version,
-                            &launcher,
-                            tool_manager,
-                        ).await;
+                            &Self::selected_launchers(&launcher_checks),
+                        );
                         
                         match result {
                             Ok(message) => {
-                                button.set_label("Install");
-                                button.remove_css_class("destructive-action");
-                                button.add_css_class("suggested-action");
-                                button.set_sensitive(true);
-                                
                                 let toast = adw::Toast::new(&message);
                                 toast.set_timeout(5);
                                 toast_overlay.add_toast(toast);
                             }
-                            Err(e) => {
-                                button.set_label("Delete");
-                                button.set_sensitive(true);
-                                
-                                let error_msg = format!("Deletion failed: {}", e);
-                                let toast = adw::Toast::new(&error_msg);
-                                toast.set_timeout(5);
-                                toast_overlay.add_toast(toast);
-                            }
+                            Err(e) => Self::error_toast(&toast_overlay, "Deletion failed", &e, move || button.emit_clicked()),
                         }
                     });
                 } else {
-                    // Handle installation
+                    // Handle installation, skipping launchers that already have this version
                     button.set_label("Installing...");
                     
+                    let targets: Vec<Launcher> = launchers.into_iter()
+                        .filter(|launcher| !installed.borrow().is_installed(&version, launcher))
+                        .collect();
+                    
                     glib::MainContext::default().spawn_local(async move {
-                        // Enter the Tokio runtime context for async operations
-                        let _guard = runtime_handle.enter();
+                        // The install runs on the Tokio runtime and reports back here
+                        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+                        let download_sender = sender.clone();
+                        let install = {
+                            let version = version.clone();
+                            let tool_manager = tool_manager.clone();
+                            Self::run_on_runtime(&runtime_handle, async move {
+                                Self::install_tool_version(
+                                    &tool_name,
+                                    &version,
+                                    &download_url,
+                                    archive_size,
+                                    archive_sha256.as_deref(),
+                                    &targets,
+                                    tool_manager,
+                                    downloader,
+                                    &db,
+                                    move |progress| {
+                                        let _ = download_sender.send(InstallUpdate::Download(progress));
+                                    },
+                                    move |progress_msg| {
+                                        let _ = sender.send(InstallUpdate::Status(progress_msg));
+                                    },
+                                ).await
+                            })
+                        };
+                        
+                        // Ends once the install has dropped both senders
+                        while let Some(update) = receiver.recv().await {
+                            match update {
+                                InstallUpdate::Download(progress) => {
+                                    // Pulse while the size is unknown
+                                    match progress.fraction() {
+                                        Some(fraction) => progress_bar.set_fraction(fraction),
+                                        None => progress_bar.pulse(),
+                                    }
+                                    progress_bar.set_text(Some(&progress.to_string()));
+                                    progress_bar.set_visible(true);
+                                }
+                                InstallUpdate::Status(progress_msg) => {
+                                    progress_bar.set_visible(false);
+                                    button.set_label(&progress_msg);
+                                }
+                            }
+                        }
+                        let result = install.await;
+                        progress_bar.set_visible(false);
                         
-                        let button_for_progress = button.clone();
-                        let result = Self::install_tool_version(
-                            &tool_name,
+                        *installed.borrow_mut() = tool_manager.install_index();
+                        Self::update_action_button(
+                            &button,
+                            &installed.borrow(),
                             &version,
-                            &download_url,
-                            &launcher,
-                            tool_manager,
-                            downloader,
-                            move |progress_msg| {
-                                // We're already in the GLib main context, so we can update directly
-                                button_for_progress.set_label(&progress_msg);
-                            },
-                        ).await;
+                            &Self::selected_launchers(&launcher_checks),
+                        );
                         
                         match result {
                             Ok(message) => {
-                                button.set_label("Delete");
-                                button.remove_css_class("suggested-action");
-                                button.add_css_class("destructive-action");
-                                button.set_sensitive(true);
-                                
                                 let toast = adw::Toast::new(&message);
                                 toast.set_timeout(5);
                                 toast_overlay.add_toast(toast);
                             }
-                            Err(e) => {
-                                button.set_label("Install");
-                                button.set_sensitive(true);
-                                
-                                let error_msg = format!("Installation failed: {}", e);
-                                let toast = adw::Toast::new(&error_msg);
-                                toast.set_timeout(5);
-                                toast_overlay.add_toast(toast);
-                            }
+                            Err(e) => Self::error_toast(&toast_overlay, "Installation failed", &e, move || button.emit_clicked()),
                         }
                     });
                 }
             });
             
-            version_row.add_suffix(&launcher_dropdown);
+            if version.prerelease {
+                let prerelease_badge = Label::new(Some("Pre-release"));
+                prerelease_badge.add_css_class("dim-label");
+                prerelease_badge.add_css_class("caption");
+                prerelease_badge.set_valign(gtk::Align::Center);
+                version_row.add_suffix(&prerelease_badge);
+            }
+            
+            version_row.add_suffix(&progress_bar);
+            version_row.add_suffix(&launcher_button);
             version_row.add_suffix(&action_button);
             expander.add_row(&version_row);
         }
         
+        // Versions are sorted newest first, so flag the tool if the top one is newer than what is installed
+        let latest = tool.versions.first().and_then(|v| v.parsed());
+        if let (Some(latest), Some(installed)) = (latest, newest_installed) {
+            if latest.is_newer_than(&installed) {
+                let update_badge = Label::new(Some("Update available"));
+                update_badge.add_css_class("dim-label");
+                update_badge.add_css_class("caption");
+                update_badge.set_valign(gtk::Align::Center);
+                expander.add_suffix(&update_badge);
+            }
+        }
+        
         list_group.add(&expander);
         expander  // Return the expander so it can be tracked for removal
     }
 
-    async fn install_tool_version<F>(
+    fn selected_launchers(launcher_checks: &[(Launcher, gtk::CheckButton)]) -> Vec<Launcher> {
+        launcher_checks.iter()
+            .filter(|(_, check)| check.is_active())
+            .map(|(launcher, _)| launcher.clone())
+            .collect()
+    }
+
+    fn launchers_label(launchers: &[Launcher]) -> String {
+        if launchers.is_empty() {
+            "No launcher".to_string()
+        } else {
+            launchers.iter().map(|l| l.to_string()).collect::<Vec<_>>().join(", ")
+        }
+    }
+
+    /// Shows "Delete" when the version is installed in every selected launcher, "Install" otherwise.
+    fn update_action_button(
+        button: &Button,
+        installed: &InstallIndex,
+        version: &str,
+        launchers: &[Launcher],
+    ) {
+        let is_installed = !launchers.is_empty()
+            && launchers.iter().all(|launcher| installed.is_installed(version, launcher));
+        
+        button.remove_css_class("suggested-action");
+        button.remove_css_class("destructive-action");
+        
+        if is_installed {
+            button.set_label("Delete");
+            button.add_css_class("destructive-action");
+        } else {
+            button.set_label("Install");
+            button.add_css_class("suggested-action");
+        }
+        button.set_sensitive(!launchers.is_empty());
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn install_tool_version<D, F>(
         tool_name: &str,
         version: &str,
         download_url: &str,
-        launcher: &crate::backend::Launcher,
-        tool_manager: Arc<Mutex<ToolManager>>,
-        downloader: Arc<Mutex<Downloader>>,
+        archive_size: Option<u64>,
+        archive_sha256: Option<&str>,
+        launchers: &[Launcher],
+        tool_manager: ToolManager,
+        downloader: Downloader,
+        db: &Database,
+        mut download_callback: D,
         mut progress_callback: F,
-    ) -> anyhow::Result<String>
+    ) -> crate::backend::Result<String>
     where
+        D: FnMut(DownloadProgress),
         F: FnMut(String),
     {
-        // Get install path
-        let install_path = tool_manager.lock()
-            .expect("Failed to lock tool manager")
-            .get_install_path(launcher)?;
-        
-        // Create install directory if it doesn't exist
-        tokio::fs::create_dir_all(&install_path).await?;
-        
-        // Determine archive filename from URL
-        let url_path = download_url.split('/').last()
-            .ok_or_else(|| anyhow::anyhow!("Invalid download URL"))?;
-        
-        // Download to temp directory
-        let temp_dir = std::env::temp_dir();
-        let archive_path = temp_dir.join(url_path);
-        
-        // Download the file with progress
-        progress_callback("Downloading (0%)".to_string());
-        downloader.lock()
-            .expect("Failed to lock downloader")
-            .download_file_with_progress(download_url, &archive_path, |progress| {
-                let msg = format!("Downloading ({:.0}%)", progress);
-                progress_callback(msg);
-            })
-            .await?;
+        if launchers.is_empty() {
+            return Err(Error::Other(format!("{} is already installed in the selected launchers", version)));
+        }
+        
+        // Get install paths
+        let targets = launchers.iter()
+            .map(|launcher| Ok((launcher.clone(), tool_manager.get_install_path(launcher)?)))
+            .collect::<crate::backend::Result<Vec<_>>>()?;
+        let origin = RunnerSource {
+            tool_name: Some(tool_name.to_string()),
+            url: Some(download_url.to_string()),
+            sha256: None,
+        };
+        
+        // Mirrors are read now so changes apply without a refresh
+        let mirrors = tool_manager.mirror_urls(tool_name, version, download_url);
         
-        // Extract to install path with specific directory name matching the version
-        progress_callback("Extracting...".to_string());
-        downloader.lock()
-            .expect("Failed to lock downloader")
-            .extract_archive_to_specific_dir(&archive_path, &install_path, version)
+        // Unpack while downloading, without saving the archive
+        if downloader.streams_extraction() {
+            let source = InstallSource::Download { url: download_url, mirrors: &mirrors, sha256: archive_sha256, size: archive_size };
+            let report = install_to_targets(&downloader, db, source, &origin, version, &targets, progress_callback).await?;
+            return report.into_message(tool_name, version);
+        }
+        
+        // Refuse to start a download that cannot be unpacked anywhere. Without a
+        // listed size the download directory is checked against the response's
+        // Content-Length, and the launchers once the archive is on disk
+        if let Some(size) = archive_size {
+            let download_dir = downloader.download_dir();
+            check_space(&space_requirements(size, Some(&download_dir), &targets))?;
+        }
+        
+        // Download the file with progress, or reuse a cached copy
+        progress_callback("Downloading...".to_string());
+        let archive = downloader
+            .fetch_archive(download_url, &mirrors, archive_sha256, &mut download_callback)
             .await?;
         
-        // Record installation in database
-        if let Ok(db) = crate::backend::Database::new() {
-            let _ = db.add_installed_runner(version, launcher);
+        let report = install_to_targets(
+            &downloader,
+            db,
+            InstallSource::Archive(&archive.path),
+            &origin,
+            version,
+            &targets,
+            progress_callback,
+        ).await;
+        
+        // Clean up downloaded archive unless the cache keeps it
+        if !archive.cached {
+            let _ = tokio::fs::remove_file(&archive.path).await;
         }
         
-        // Clean up downloaded archive
-        let _ = tokio::fs::remove_file(&archive_path).await;
+        report?.into_message(tool_name, version)
+    }
+
+    /// Installs a runner from an archive or directory picked on disk.
+    async fn install_local_source(
+        source_path: &std::path::Path,
+        version: &str,
+        launchers: &[Launcher],
+        tool_manager: ToolManager,
+        downloader: Downloader,
+        db: &Database,
+    ) -> crate::backend::Result<String> {
+        let targets = launchers.iter()
+            .map(|launcher| Ok((launcher.clone(), tool_manager.get_install_path(launcher)?)))
+            .collect::<crate::backend::Result<Vec<_>>>()?;
         
-        Ok(format!("{} {} installed successfully!", tool_name, version))
+        install_to_targets(
+            &downloader,
+            db,
+            InstallSource::from_path(source_path),
+            &RunnerSource::default(),
+            version,
+            &targets,
+            |_| {},
+        ).await?.into_message("Local runner", version)
     }
 
     async fn delete_tool_version(
         version: &str,
-        launcher: &crate::backend::Launcher,
-        tool_manager: Arc<Mutex<ToolManager>>,
-    ) -> anyhow::Result<String> {
-        // Get install path
-        let install_path = tool_manager.lock()
-            .expect("Failed to lock tool manager")
-            .get_install_path(launcher)?;
-        
-        // Find the directory for this version
-        let version_path = install_path.join(version);
-        
-        if version_path.exists() {
-            // Delete the directory
-            tokio::fs::remove_dir_all(&version_path).await?;
-            
-            // Remove from database
-            if let Ok(db) = crate::backend::Database::new() {
-                let _ = db.remove_installed_runner(version, launcher);
-            }
-            
-            Ok(format!("{} deleted successfully!", version))
+        launchers: &[Launcher],
+        tool_manager: ToolManager,
+        db: &Database,
+    ) -> crate::backend::Result<String> {
+        let deleted = uninstall(&tool_manager, db, version, launchers).await?;
+        
+        if deleted.is_empty() {
+            Err(Error::Other(format!("Tool version {} not found", version)))
         } else {
-            Err(anyhow::anyhow!("Tool version {} not found", version))
+            let names: Vec<String> = deleted.iter().map(|launcher| launcher.to_string()).collect();
+            Ok(format!("{} deleted from {}!", version, names.join(", ")))
         }
     }
 
-    fn setup_menu(menu_button: &gtk::MenuButton, window: &adw::ApplicationWindow, toast_overlay: &adw::ToastOverlay, tool_manager: Arc<Mutex<ToolManager>>) {
+    fn setup_menu(menu_button: &gtk::MenuButton, window: &adw::ApplicationWindow, toast_overlay: &adw::ToastOverlay, tool_manager: ToolManager, downloader: Downloader, db: Database, runtime_handle: Arc<tokio::runtime::Handle>) {
         let menu = gtk::gio::Menu::new();
         
+        menu.append(Some("Install from File…"), Some("app.install-file"));
+        menu.append(Some("Install from Folder…"), Some("app.install-folder"));
+        menu.append(Some("Export Runners…"), Some("app.export-runners"));
+        menu.append(Some("Import Runners…"), Some("app.import-runners"));
         menu.append(Some("Preferences"), Some("app.preferences"));
         menu.append(Some("About"), Some("app.about"));
         
         menu_button.set_menu_model(Some(&menu));
         
         // Create actions
+        let install_file_action = gtk::gio::SimpleAction::new("install-file", None);
+        let window_clone = window.clone();
+        let toast_overlay_clone = toast_overlay.clone();
+        let tool_manager_clone = tool_manager.clone();
+        let downloader_clone = downloader.clone();
+        let db_clone = db.clone();
+        let runtime_handle_clone = runtime_handle.clone();
+        install_file_action.connect_activate(move |_, _| {
+            let filter = gtk::FileFilter::new();
+            filter.set_name(Some("Runner archives"));
+            for pattern in ["*.tar.gz", "*.tgz", "*.tar.xz"] {
+                filter.add_pattern(pattern);
+            }
+            let filters = gtk::gio::ListStore::new::<gtk::FileFilter>();
+            filters.append(&filter);
+            
+            let file_dialog = gtk::FileDialog::builder()
+                .title("Select Runner Archive")
+                .modal(true)
+                .filters(&filters)
+                .build();
+            
+            let window = window_clone.clone();
+            let toast_overlay = toast_overlay_clone.clone();
+            let tool_manager = tool_manager_clone.clone();
+            let downloader = downloader_clone.clone();
+            let db = db_clone.clone();
+            let runtime_handle = runtime_handle_clone.clone();
+            file_dialog.open(Some(&window_clone), gtk::gio::Cancellable::NONE, move |result| {
+                if let Some(path) = result.ok().and_then(|file| file.path()) {
+                    Self::show_local_install_dialog(&window, &toast_overlay, path, tool_manager, downloader, db, runtime_handle);
+                }
+            });
+        });
+        
+        let install_folder_action = gtk::gio::SimpleAction::new("install-folder", None);
+        let window_clone = window.clone();
+        let toast_overlay_clone = toast_overlay.clone();
+        let tool_manager_clone = tool_manager.clone();
+        let downloader_clone = downloader.clone();
+        let db_clone = db.clone();
+        let runtime_handle_clone = runtime_handle.clone();
+        install_folder_action.connect_activate(move |_, _| {
+            let file_dialog = gtk::FileDialog::builder()
+                .title("Select Runner Directory")
+                .modal(true)
+                .build();
+            
+            let window = window_clone.clone();
+            let toast_overlay = toast_overlay_clone.clone();
+            let tool_manager = tool_manager_clone.clone();
+            let downloader = downloader_clone.clone();
+            let db = db_clone.clone();
+            let runtime_handle = runtime_handle_clone.clone();
+            file_dialog.select_folder(Some(&window_clone), gtk::gio::Cancellable::NONE, move |result| {
+                if let Some(path) = result.ok().and_then(|folder| folder.path()) {
+                    Self::show_local_install_dialog(&window, &toast_overlay, path, tool_manager, downloader, db, runtime_handle);
+                }
+            });
+        });
+        
+        let export_action = gtk::gio::SimpleAction::new("export-runners", None);
+        let window_clone = window.clone();
+        let toast_overlay_clone = toast_overlay.clone();
+        let db_clone = db.clone();
+        export_action.connect_activate(move |_, _| {
+            let file_dialog = gtk::FileDialog::builder()
+                .title("Export Runners")
+                .modal(true)
+                .initial_name("runners.toml")
+                .build();
+            
+            let toast_overlay = toast_overlay_clone.clone();
+            let db = db_clone.clone();
+            file_dialog.save(Some(&window_clone), gtk::gio::Cancellable::NONE, move |result| {
+                if let Some(path) = result.ok().and_then(|file| file.path()) {
+                    let result = db.list_installed_runners()
+                        .and_then(|runners| {
+                            RunnerManifest::from_installed(&runners).save(&path)?;
+                            Ok(runners.len())
+                        });
+                    let message = match result {
+                        Ok(count) => format!("Exported {} runners", count),
+                        Err(e) => format!("Export failed: {}", e.user_message()),
+                    };
+                    let toast = adw::Toast::new(&message);
+                    toast.set_timeout(3);
+                    toast_overlay.add_toast(toast);
+                }
+            });
+        });
+        
+        let import_action = gtk::gio::SimpleAction::new("import-runners", None);
+        let window_clone = window.clone();
+        let toast_overlay_clone = toast_overlay.clone();
+        let tool_manager_clone = tool_manager.clone();
+        let downloader_clone = downloader.clone();
+        let db_clone = db.clone();
+        let runtime_handle_clone = runtime_handle.clone();
+        import_action.connect_activate(move |_, _| {
+            let file_dialog = gtk::FileDialog::builder()
+                .title("Import Runners")
+                .modal(true)
+                .build();
+            
+            let window = window_clone.clone();
+            let toast_overlay = toast_overlay_clone.clone();
+            let tool_manager = tool_manager_clone.clone();
+            let downloader = downloader_clone.clone();
+            let db = db_clone.clone();
+            let runtime_handle = runtime_handle_clone.clone();
+            file_dialog.open(Some(&window_clone), gtk::gio::Cancellable::NONE, move |result| {
+                let Some(path) = result.ok().and_then(|file| file.path()) else {
+                    return;
+                };
+                match RunnerManifest::load(&path) {
+                    Ok(manifest) => Self::show_import_dialog(&window, &toast_overlay, manifest, tool_manager, downloader, db, runtime_handle),
+                    Err(e) => {
+                        let toast = adw::Toast::new(&format!("Could not read manifest: {}", e.user_message()));
+                        toast.set_timeout(5);
+                        toast_overlay.add_toast(toast);
+                    }
+                }
+            });
+        });
+        
         let preferences_action = gtk::gio::SimpleAction::new("preferences", None);
         let window_clone = window.clone();
         let toast_overlay_clone = toast_overlay.clone();
         let tool_manager_clone = tool_manager.clone();
         preferences_action.connect_activate(move |_, _| {
-            Self::show_preferences_dialog(&window_clone, &toast_overlay_clone, tool_manager_clone.clone());
+            Self::show_preferences_dialog(&window_clone, &toast_overlay_clone, tool_manager_clone.clone(), downloader.clone(), db.clone());
         });
         
         let about_action = gtk::gio::SimpleAction::new("about", None);
@@ -547,11 +923,186 @@ impl MainWindow {
         });
         
         let app = window.application().unwrap();
+        app.add_action(&install_file_action);
+        app.add_action(&install_folder_action);
+        app.add_action(&export_action);
+        app.add_action(&import_action);
         app.add_action(&preferences_action);
         app.add_action(&about_action);
     }
 
-    fn show_preferences_dialog(window: &adw::ApplicationWindow, toast_overlay: &adw::ToastOverlay, tool_manager: Arc<Mutex<ToolManager>>) {
+    /// Asks for a name and target launchers before installing a local archive or directory.
+    fn show_local_install_dialog(
+        window: &adw::ApplicationWindow,
+        toast_overlay: &adw::ToastOverlay,
+        source_path: std::path::PathBuf,
+        tool_manager: ToolManager,
+        downloader: Downloader,
+        db: Database,
+        runtime_handle: Arc<tokio::runtime::Handle>,
+    ) {
+        let dialog = adw::AlertDialog::builder()
+            .heading("Install Local Runner")
+            .body(source_path.display().to_string())
+            .build();
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("install", "Install");
+        dialog.set_response_appearance("install", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("install"));
+        dialog.set_close_response("cancel");
+        
+        let content = Box::new(Orientation::Vertical, 6);
+        
+        // Directory name the runner is installed under
+        let name_entry = gtk::Entry::builder()
+            .placeholder_text("Version name")
+            .text(InstallSource::from_path(&source_path).default_version_name().unwrap_or_default())
+            .build();
+        content.append(&name_entry);
+        
+        let launcher_checks: Vec<(Launcher, gtk::CheckButton)> = Launcher::ALL.iter()
+            .map(|launcher| {
+                let check = gtk::CheckButton::with_label(&launcher.to_string());
+                check.set_active(*launcher == Launcher::Steam);
+                content.append(&check);
+                (launcher.clone(), check)
+            })
+            .collect();
+        dialog.set_extra_child(Some(&content));
+        
+        let toast_overlay = toast_overlay.clone();
+        dialog.connect_response(Some("install"), move |_, _| {
+            let version = name_entry.text().trim().to_string();
+            let launchers = Self::selected_launchers(&launcher_checks);
+            let source_path = source_path.clone();
+            let tool_manager = tool_manager.clone();
+            let downloader = downloader.clone();
+            let db = db.clone();
+            let runtime_handle = runtime_handle.clone();
+            let toast_overlay = toast_overlay.clone();
+            
+            glib::MainContext::default().spawn_local(async move {
+                let result = if let Err(e) = check_version_name(&version) {
+                    Err(e)
+                } else if launchers.is_empty() {
+                    Err(Error::Other("No launcher selected".to_string()))
+                } else {
+                    Self::run_on_runtime(&runtime_handle, async move {
+                        Self::install_local_source(&source_path, &version, &launchers, tool_manager, downloader, &db).await
+                    }).await
+                };
+                
+                let message = match result {
+                    Ok(message) => message,
+                    Err(e) => format!("Installation failed: {}", e.user_message()),
+                };
+                let toast = adw::Toast::new(&message);
+                toast.set_timeout(5);
+                toast_overlay.add_toast(toast);
+            });
+        });
+        
+        dialog.present(Some(window));
+    }
+
+    /// Offers to install what a manifest lists, or to also remove what it doesn't.
+    fn show_import_dialog(
+        window: &adw::ApplicationWindow,
+        toast_overlay: &adw::ToastOverlay,
+        manifest: RunnerManifest,
+        tool_manager: ToolManager,
+        downloader: Downloader,
+        db: Database,
+        runtime_handle: Arc<tokio::runtime::Handle>,
+    ) {
+        let installed = match db.list_installed_runners() {
+            Ok(installed) => installed,
+            Err(e) => {
+                let toast = adw::Toast::new(&format!("Could not read installed runners: {}", e));
+                toast.set_timeout(5);
+                toast_overlay.add_toast(toast);
+                return;
+            }
+        };
+        let install_plan = manifest.plan(&installed, false);
+        let sync_plan = manifest.plan(&installed, true);
+        
+        let dialog = adw::AlertDialog::builder()
+            .heading("Import Runners")
+            .body(format!(
+                "{} of {} runners are missing and will be installed. Syncing also removes {} runners not in the manifest.",
+                install_plan.install.len(),
+                manifest.runners.len(),
+                sync_plan.remove.len(),
+            ))
+            .build();
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("install", "Install Missing");
+        dialog.add_response("sync", "Sync");
+        dialog.set_response_appearance("install", adw::ResponseAppearance::Suggested);
+        dialog.set_response_appearance("sync", adw::ResponseAppearance::Destructive);
+        dialog.set_response_enabled("install", !install_plan.is_empty());
+        dialog.set_response_enabled("sync", !sync_plan.is_empty());
+        dialog.set_close_response("cancel");
+        
+        let toast_overlay = toast_overlay.clone();
+        let plans = Rc::new(RefCell::new(Some((install_plan, sync_plan))));
+        dialog.connect_response(None, move |_, response| {
+            let Some((install_plan, sync_plan)) = plans.borrow_mut().take() else {
+                return;
+            };
+            let plan = match response {
+                "install" => install_plan,
+                "sync" => sync_plan,
+                _ => return,
+            };
+            let tool_manager = tool_manager.clone();
+            let downloader = downloader.clone();
+            let db = db.clone();
+            let runtime_handle = runtime_handle.clone();
+            let toast_overlay = toast_overlay.clone();
+            
+            glib::MainContext::default().spawn_local(async move {
+                let toast = adw::Toast::new("Applying runner manifest...");
+                toast.set_timeout(3);
+                toast_overlay.add_toast(toast);
+                
+                let result = Self::run_on_runtime(&runtime_handle, async move {
+                    Self::apply_manifest_plan(&plan, tool_manager, downloader, &db).await
+                }).await;
+                let message = match result {
+                    Ok(report) => report.message(),
+                    Err(e) => format!("Import failed: {}", e.user_message()),
+                };
+                let toast = adw::Toast::new(&message);
+                toast.set_timeout(5);
+                toast_overlay.add_toast(toast);
+            });
+        });
+        
+        dialog.present(Some(window));
+    }
+
+    async fn apply_manifest_plan(
+        plan: &SyncPlan,
+        tool_manager: ToolManager,
+        downloader: Downloader,
+        db: &Database,
+    ) -> crate::backend::Result<SyncReport> {
+        let install_paths = Launcher::ALL.iter()
+            .filter_map(|launcher| Some((launcher.clone(), tool_manager.get_install_path(launcher).ok()?)))
+            .collect::<Vec<_>>();
+        
+        apply_plan(
+            plan,
+            &downloader,
+            db,
+            &install_paths,
+            |_| {},
+        ).await
+    }
+
+    fn show_preferences_dialog(window: &adw::ApplicationWindow, toast_overlay: &adw::ToastOverlay, tool_manager: ToolManager, downloader: Downloader, db: Database) {
         let dialog = adw::PreferencesWindow::builder()
             .transient_for(window)
             .modal(true)
@@ -573,21 +1124,15 @@ impl MainWindow {
             .build();
         
         // Get current paths
-        let current_steam_path = {
-            let manager = tool_manager.lock().expect("Failed to lock tool manager");
-            manager.get_install_path(&crate::backend::Launcher::Steam)
-                .ok()
-                .and_then(|p| p.to_str().map(|s| s.to_string()))
-                .unwrap_or_else(|| "~/.steam/root/compatibilitytools.d".to_string())
-        };
+        let current_steam_path = tool_manager.get_install_path(&crate::backend::Launcher::Steam)
+            .ok()
+            .and_then(|p| p.to_str().map(|s| s.to_string()))
+            .unwrap_or_else(|| "~/.steam/root/compatibilitytools.d".to_string());
         
-        let current_lutris_path = {
-            let manager = tool_manager.lock().expect("Failed to lock tool manager");
-            manager.get_install_path(&crate::backend::Launcher::Lutris)
-                .ok()
-                .and_then(|p| p.to_str().map(|s| s.to_string()))
-                .unwrap_or_else(|| "~/.local/share/lutris/runners/wine".to_string())
-        };
+        let current_lutris_path = tool_manager.get_install_path(&crate::backend::Launcher::Lutris)
+            .ok()
+            .and_then(|p| p.to_str().map(|s| s.to_string()))
+            .unwrap_or_else(|| "~/.local/share/lutris/runners/wine".to_string());
         
         // Steam path row with directory picker
         let steam_row = adw::ActionRow::builder()
@@ -618,11 +1163,16 @@ impl MainWindow {
             file_dialog.select_folder(Some(&window_clone), gtk::gio::Cancellable::NONE, move |result| {
                 if let Ok(folder) = result {
                     if let Some(path) = folder.path() {
-                        tool_manager.lock().expect("Failed to lock").set_steam_path(Some(path.clone()));
-                        if let Some(path_str) = path.to_str() {
-                            steam_row.set_subtitle(path_str);
-                        }
-                        let toast = adw::Toast::new("Steam path updated");
+                        let message = match tool_manager.set_steam_path(Some(path.clone())) {
+                            Ok(()) => {
+                                if let Some(path_str) = path.to_str() {
+                                    steam_row.set_subtitle(path_str);
+                                }
+                                "Steam path updated".to_string()
+                            }
+                            Err(e) => format!("Failed to save Steam path: {}", e),
+                        };
+                        let toast = adw::Toast::new(&message);
                         toast.set_timeout(3);
                         toast_overlay.add_toast(toast);
                     }
@@ -644,7 +1194,12 @@ impl MainWindow {
         let toast_overlay_steam_reset = toast_overlay.clone();
         let steam_row_reset = steam_row.clone();
         steam_reset_button.connect_clicked(move |_| {
-            tool_manager_steam_reset.lock().expect("Failed to lock").set_steam_path(None);
+            if let Err(e) = tool_manager_steam_reset.set_steam_path(None) {
+                let toast = adw::Toast::new(&format!("Failed to reset Steam path: {}", e));
+                toast.set_timeout(5);
+                toast_overlay_steam_reset.add_toast(toast);
+                return;
+            }
             let default_path = dirs::home_dir()
                 .map(|h| h.join(".steam/root/compatibilitytools.d"))
                 .and_then(|p| p.to_str().map(|s| s.to_string()))
@@ -687,11 +1242,16 @@ impl MainWindow {
             file_dialog.select_folder(Some(&window_clone), gtk::gio::Cancellable::NONE, move |result| {
                 if let Ok(folder) = result {
                     if let Some(path) = folder.path() {
-                        tool_manager.lock().expect("Failed to lock").set_lutris_path(Some(path.clone()));
-                        if let Some(path_str) = path.to_str() {
-                            lutris_row.set_subtitle(path_str);
-                        }
-                        let toast = adw::Toast::new("Lutris path updated");
+                        let message = match tool_manager.set_lutris_path(Some(path.clone())) {
+                            Ok(()) => {
+                                if let Some(path_str) = path.to_str() {
+                                    lutris_row.set_subtitle(path_str);
+                                }
+                                "Lutris path updated".to_string()
+                            }
+                            Err(e) => format!("Failed to save Lutris path: {}", e),
+                        };
+                        let toast = adw::Toast::new(&message);
                         toast.set_timeout(3);
                         toast_overlay.add_toast(toast);
                     }
@@ -713,7 +1273,12 @@ impl MainWindow {
         let toast_overlay_lutris_reset = toast_overlay.clone();
         let lutris_row_reset = lutris_row.clone();
         lutris_reset_button.connect_clicked(move |_| {
-            tool_manager_lutris_reset.lock().expect("Failed to lock").set_lutris_path(None);
+            if let Err(e) = tool_manager_lutris_reset.set_lutris_path(None) {
+                let toast = adw::Toast::new(&format!("Failed to reset Lutris path: {}", e));
+                toast.set_timeout(5);
+                toast_overlay_lutris_reset.add_toast(toast);
+                return;
+            }
             let default_path = dirs::home_dir()
                 .map(|h| h.join(".local/share/lutris/runners/wine"))
                 .and_then(|p| p.to_str().map(|s| s.to_string()))
@@ -729,6 +1294,496 @@ impl MainWindow {
         
         page.add(&paths_group);
         
+        // Release channel group
+        let channel_group = adw::PreferencesGroup::builder()
+            .title("Release Channels")
+            .description("Choose which releases are listed for each tool. Takes effect on the next refresh.")
+            .build();
+        
+        for tool_name in ["GE-Proton", "Wine-GE", "Spritz-Wine", "dwproton"] {
+            let channel_row = adw::ActionRow::builder()
+                .title(tool_name)
+                .subtitle("Include pre-releases")
+                .build();
+            
+            let current_channel = tool_manager.get_release_channel(tool_name);
+            
+            let channel_switch = gtk::Switch::builder()
+                .valign(gtk::Align::Center)
+                .active(current_channel == crate::backend::ReleaseChannel::PreRelease)
+                .build();
+            
+            let tool_manager_channel = tool_manager.clone();
+            let toast_overlay_channel = toast_overlay.clone();
+            channel_switch.connect_active_notify(move |switch| {
+                let channel = if switch.is_active() {
+                    crate::backend::ReleaseChannel::PreRelease
+                } else {
+                    crate::backend::ReleaseChannel::Stable
+                };
+                let result = tool_manager_channel.set_release_channel(tool_name, channel);
+                if let Err(e) = result {
+                    let toast = adw::Toast::new(&format!("Failed to save release channel: {}", e));
+                    toast.set_timeout(5);
+                    toast_overlay_channel.add_toast(toast);
+                }
+            });
+            
+            channel_row.add_suffix(&channel_switch);
+            channel_row.set_activatable_widget(Some(&channel_switch));
+            channel_group.add(&channel_row);
+        }
+        
+        page.add(&channel_group);
+        
+        // Download cache group
+        let cache_group = adw::PreferencesGroup::builder()
+            .title("Download Cache")
+            .description("Keep downloaded archives so reinstalls and installs into other launchers work offline")
+            .build();
+        
+        let cache_enabled = db.get_archive_cache_enabled().unwrap_or(true);
+        let cache_limit_mb = db.get_archive_cache_limit_mb()
+            .unwrap_or(crate::backend::database::DEFAULT_ARCHIVE_CACHE_LIMIT_MB);
+        
+        let cache_enabled_row = adw::ActionRow::builder()
+            .title("Keep Downloaded Archives")
+            .build();
+        let cache_enabled_switch = gtk::Switch::builder()
+            .valign(gtk::Align::Center)
+            .active(cache_enabled)
+            .build();
+        cache_enabled_row.add_suffix(&cache_enabled_switch);
+        cache_enabled_row.set_activatable_widget(Some(&cache_enabled_switch));
+        cache_group.add(&cache_enabled_row);
+        
+        let cache_limit_row = adw::ActionRow::builder()
+            .title("Cache Size Limit")
+            .subtitle("In MiB; least recently used archives are removed first")
+            .build();
+        let cache_limit_spin = gtk::SpinButton::with_range(256.0, 65536.0, 256.0);
+        cache_limit_spin.set_value(cache_limit_mb as f64);
+        cache_limit_spin.set_valign(gtk::Align::Center);
+        cache_limit_row.add_suffix(&cache_limit_spin);
+        cache_group.add(&cache_limit_row);
+        
+        let cache_size_mb = downloader.cache_size() / (1024 * 1024);
+        let cache_clear_row = adw::ActionRow::builder()
+            .title("Cached Archives")
+            .subtitle(format!("{} MiB in use", cache_size_mb))
+            .build();
+        let cache_clear_button = Button::builder()
+            .label("Clear")
+            .valign(gtk::Align::Center)
+            .build();
+        cache_clear_row.add_suffix(&cache_clear_button);
+        cache_group.add(&cache_clear_row);
+        
+        let streaming_row = adw::ActionRow::builder()
+            .title("Extract While Downloading")
+            .subtitle("Unpack archives as they arrive instead of saving them first; only used when archives are not kept")
+            .build();
+        let streaming_switch = gtk::Switch::builder()
+            .valign(gtk::Align::Center)
+            .active(db.get_streaming_extraction().unwrap_or(false))
+            .build();
+        streaming_row.add_suffix(&streaming_switch);
+        streaming_row.set_activatable_widget(Some(&streaming_switch));
+        cache_group.add(&streaming_row);
+        
+        let downloader_streaming = downloader.clone();
+        let db_streaming = db.clone();
+        let toast_overlay_streaming = toast_overlay.clone();
+        streaming_switch.connect_active_notify(move |switch| {
+            let enabled = switch.is_active();
+            match db_streaming.set_streaming_extraction(enabled) {
+                Ok(()) => downloader_streaming.set_streaming(enabled),
+                Err(e) => {
+                    let toast = adw::Toast::new(&format!("Failed to save download settings: {}", e));
+                    toast.set_timeout(5);
+                    toast_overlay_streaming.add_toast(toast);
+                }
+            }
+        });
+        
+        // Temporary directory row with directory picker
+        let temp_dir_row = adw::ActionRow::builder()
+            .title("Temporary Directory")
+            .subtitle(downloader.temp_dir().to_string_lossy())
+            .build();
+        temp_dir_row.set_tooltip_text(Some("Where archives are downloaded when they are not kept"));
+        
+        let temp_dir_button = Button::builder()
+            .icon_name("folder-open-symbolic")
+            .valign(gtk::Align::Center)
+            .build();
+        temp_dir_button.add_css_class("flat");
+        
+        let downloader_temp = downloader.clone();
+        let db_temp = db.clone();
+        let toast_overlay_temp = toast_overlay.clone();
+        let temp_dir_row_clone = temp_dir_row.clone();
+        let window_clone = window.clone();
+        temp_dir_button.connect_clicked(move |_| {
+            let file_dialog = gtk::FileDialog::builder()
+                .title("Select Temporary Directory")
+                .modal(true)
+                .build();
+            
+            let downloader = downloader_temp.clone();
+            let db = db_temp.clone();
+            let toast_overlay = toast_overlay_temp.clone();
+            let temp_dir_row = temp_dir_row_clone.clone();
+            
+            file_dialog.select_folder(Some(&window_clone), gtk::gio::Cancellable::NONE, move |result| {
+                if let Ok(folder) = result {
+                    if let Some(path) = folder.path() {
+                        let message = match db.set_temp_dir(Some(&path)) {
+                            Ok(()) => {
+                                temp_dir_row.set_subtitle(&path.to_string_lossy());
+                                downloader.set_temp_dir(Some(path));
+                                "Temporary directory updated".to_string()
+                            }
+                            Err(e) => format!("Failed to save temporary directory: {}", e),
+                        };
+                        let toast = adw::Toast::new(&message);
+                        toast.set_timeout(3);
+                        toast_overlay.add_toast(toast);
+                    }
+                }
+            });
+        });
+        temp_dir_row.add_suffix(&temp_dir_button);
+        
+        let temp_dir_reset_button = Button::builder()
+            .icon_name("edit-clear-symbolic")
+            .valign(gtk::Align::Center)
+            .tooltip_text("Reset to default")
+            .build();
+        temp_dir_reset_button.add_css_class("flat");
+        
+        let downloader_temp_reset = downloader.clone();
+        let db_temp_reset = db.clone();
+        let toast_overlay_temp_reset = toast_overlay.clone();
+        let temp_dir_row_reset = temp_dir_row.clone();
+        temp_dir_reset_button.connect_clicked(move |_| {
+            if let Err(e) = db_temp_reset.set_temp_dir(None) {
+                let toast = adw::Toast::new(&format!("Failed to reset temporary directory: {}", e));
+                toast.set_timeout(5);
+                toast_overlay_temp_reset.add_toast(toast);
+                return;
+            }
+            downloader_temp_reset.set_temp_dir(None);
+            temp_dir_row_reset.set_subtitle(&downloader_temp_reset.temp_dir().to_string_lossy());
+            let toast = adw::Toast::new("Temporary directory reset to default");
+            toast.set_timeout(3);
+            toast_overlay_temp_reset.add_toast(toast);
+        });
+        temp_dir_row.add_suffix(&temp_dir_reset_button);
+        cache_group.add(&temp_dir_row);
+        
+        let downloader_enabled = downloader.clone();
+        let db_enabled = db.clone();
+        let toast_overlay_enabled = toast_overlay.clone();
+        let cache_limit_spin_enabled = cache_limit_spin.clone();
+        cache_enabled_switch.connect_active_notify(move |switch| {
+            let enabled = switch.is_active();
+            let limit_mb = cache_limit_spin_enabled.value() as u64;
+            let result = db_enabled.set_archive_cache_enabled(enabled).and_then(|()| {
+                downloader_enabled.configure_cache(enabled, limit_mb * 1024 * 1024)
+            });
+            if let Err(e) = result {
+                let toast = adw::Toast::new(&format!("Failed to save cache settings: {}", e));
+                toast.set_timeout(5);
+                toast_overlay_enabled.add_toast(toast);
+            }
+        });
+        
+        let downloader_limit = downloader.clone();
+        let db_limit = db.clone();
+        let toast_overlay_limit = toast_overlay.clone();
+        let cache_enabled_switch_limit = cache_enabled_switch.clone();
+        cache_limit_spin.connect_value_changed(move |spin| {
+            let limit_mb = spin.value() as u64;
+            let result = db_limit.set_archive_cache_limit_mb(limit_mb).and_then(|()| {
+                downloader_limit.configure_cache(cache_enabled_switch_limit.is_active(), limit_mb * 1024 * 1024)
+            });
+            if let Err(e) = result {
+                let toast = adw::Toast::new(&format!("Failed to save cache settings: {}", e));
+                toast.set_timeout(5);
+                toast_overlay_limit.add_toast(toast);
+            }
+        });
+        
+        let downloader_clear = downloader.clone();
+        let toast_overlay_clear = toast_overlay.clone();
+        let cache_clear_row_clone = cache_clear_row.clone();
+        cache_clear_button.connect_clicked(move |_| {
+            let result = downloader_clear.clear_cache();
+            let message = match result {
+                Ok(()) => {
+                    cache_clear_row_clone.set_subtitle("0 MiB in use");
+                    "Download cache cleared".to_string()
+                }
+                Err(e) => format!("Failed to clear download cache: {}", e),
+            };
+            let toast = adw::Toast::new(&message);
+            toast.set_timeout(3);
+            toast_overlay_clear.add_toast(toast);
+        });
+        
+        page.add(&cache_group);
+        
+        // Network group
+        let network_group = adw::PreferencesGroup::builder()
+            .title("Network")
+            .description("Failed downloads are retried, waiting longer after each attempt")
+            .build();
+        
+        let network_settings = db.get_network_settings().unwrap_or_default();
+        let network_spin = |title: &str, subtitle: &str, min: f64, max: f64, value: f64| {
+            let row = adw::ActionRow::builder()
+                .title(title)
+                .subtitle(subtitle)
+                .build();
+            let spin = gtk::SpinButton::with_range(min, max, 1.0);
+            spin.set_value(value);
+            spin.set_valign(gtk::Align::Center);
+            row.add_suffix(&spin);
+            network_group.add(&row);
+            spin
+        };
+        let connect_timeout_spin = network_spin(
+            "Connection Timeout",
+            "Seconds to wait for a server to answer",
+            1.0, 300.0,
+            network_settings.connect_timeout.as_secs() as f64,
+        );
+        let read_timeout_spin = network_spin(
+            "Read Timeout",
+            "Seconds a download may stall before it is retried",
+            1.0, 600.0,
+            network_settings.read_timeout.as_secs() as f64,
+        );
+        let attempts_spin = network_spin(
+            "Download Attempts",
+            "Including the first one",
+            1.0, 10.0,
+            network_settings.max_attempts as f64,
+        );
+        let rate_limit_spin = network_spin(
+            "Download Speed Limit",
+            "KiB/s shared by all downloads, 0 for unlimited",
+            0.0, 1_000_000.0,
+            downloader.rate_limit().map(|limit| limit / 1024).unwrap_or(0) as f64,
+        );
+        
+        let network_context = (db.clone(), downloader.clone(), toast_overlay.clone());
+        let (db_connect, downloader_connect, toast_overlay_connect) = network_context.clone();
+        connect_timeout_spin.connect_value_changed(move |spin| {
+            let secs = spin.value() as u64;
+            Self::update_network_settings(&db_connect, &downloader_connect, &toast_overlay_connect, |settings| {
+                settings.connect_timeout = std::time::Duration::from_secs(secs);
+            });
+        });
+        let (db_read, downloader_read, toast_overlay_read) = network_context.clone();
+        read_timeout_spin.connect_value_changed(move |spin| {
+            let secs = spin.value() as u64;
+            Self::update_network_settings(&db_read, &downloader_read, &toast_overlay_read, |settings| {
+                settings.read_timeout = std::time::Duration::from_secs(secs);
+            });
+        });
+        let (db_attempts, downloader_attempts, toast_overlay_attempts) = network_context.clone();
+        attempts_spin.connect_value_changed(move |spin| {
+            let attempts = spin.value() as u32;
+            Self::update_network_settings(&db_attempts, &downloader_attempts, &toast_overlay_attempts, |settings| {
+                settings.max_attempts = attempts;
+            });
+        });
+        let (db_rate, downloader_rate, toast_overlay_rate) = network_context.clone();
+        rate_limit_spin.connect_value_changed(move |spin| {
+            let limit_kib = Some(spin.value() as u64).filter(|limit| *limit > 0);
+            // Running downloads slow down or speed up right away
+            downloader_rate.set_rate_limit(limit_kib.map(|limit| limit * 1024));
+            if let Err(e) = db_rate.set_download_rate_limit_kib(limit_kib) {
+                let toast = adw::Toast::new(&format!("Failed to save speed limit: {}", e));
+                toast.set_timeout(3);
+                toast_overlay_rate.add_toast(toast);
+            }
+        });
+        
+        page.add(&network_group);
+        
+        // Proxy and certificates group
+        let proxy_group = adw::PreferencesGroup::builder()
+            .title("Proxy and Certificates")
+            .description("Used for release lists and downloads")
+            .build();
+        
+        let proxy_row = adw::EntryRow::builder()
+            .title("Proxy URL")
+            .text(network_settings.proxy.clone().unwrap_or_default())
+            .show_apply_button(true)
+            .build();
+        proxy_row.set_tooltip_text(Some("e.g. http://proxy.example.com:3128; leave empty to use the HTTP_PROXY environment variables"));
+        let (db_proxy, downloader_proxy, toast_overlay_proxy) = network_context.clone();
+        proxy_row.connect_apply(move |row| {
+            let proxy = Some(row.text().trim().to_string()).filter(|url| !url.is_empty());
+            Self::update_network_settings(&db_proxy, &downloader_proxy, &toast_overlay_proxy, |settings| {
+                settings.proxy = proxy;
+            });
+        });
+        proxy_group.add(&proxy_row);
+        
+        let no_proxy_row = adw::EntryRow::builder()
+            .title("Bypass Proxy For")
+            .text(network_settings.no_proxy.clone().unwrap_or_default())
+            .show_apply_button(true)
+            .build();
+        no_proxy_row.set_tooltip_text(Some("Comma-separated hosts, domains and IP ranges, e.g. localhost,.corp.example.com,10.0.0.0/8"));
+        let (db_no_proxy, downloader_no_proxy, toast_overlay_no_proxy) = network_context.clone();
+        no_proxy_row.connect_apply(move |row| {
+            let no_proxy = Some(row.text().trim().to_string()).filter(|hosts| !hosts.is_empty());
+            Self::update_network_settings(&db_no_proxy, &downloader_no_proxy, &toast_overlay_no_proxy, |settings| {
+                settings.no_proxy = no_proxy;
+            });
+        });
+        proxy_group.add(&no_proxy_row);
+        
+        // CA bundle row with file picker
+        let ca_bundle_row = adw::ActionRow::builder()
+            .title("Extra CA Certificates")
+            .subtitle(
+                network_settings.ca_bundle.as_ref()
+                    .map(|path| path.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "None, only system certificates are trusted".to_string()),
+            )
+            .build();
+        
+        let ca_bundle_button = Button::builder()
+            .icon_name("document-open-symbolic")
+            .valign(gtk::Align::Center)
+            .build();
+        ca_bundle_button.add_css_class("flat");
+        
+        let (db_ca, downloader_ca, toast_overlay_ca) = network_context.clone();
+        let ca_bundle_row_clone = ca_bundle_row.clone();
+        let window_clone = window.clone();
+        ca_bundle_button.connect_clicked(move |_| {
+            let file_dialog = gtk::FileDialog::builder()
+                .title("Select CA Certificate Bundle (PEM)")
+                .modal(true)
+                .build();
+            
+            let db = db_ca.clone();
+            let downloader = downloader_ca.clone();
+            let toast_overlay = toast_overlay_ca.clone();
+            let ca_bundle_row = ca_bundle_row_clone.clone();
+            
+            file_dialog.open(Some(&window_clone), gtk::gio::Cancellable::NONE, move |result| {
+                if let Some(path) = result.ok().and_then(|file| file.path()) {
+                    let subtitle = path.to_string_lossy().to_string();
+                    if Self::update_network_settings(&db, &downloader, &toast_overlay, |settings| settings.ca_bundle = Some(path)) {
+                        ca_bundle_row.set_subtitle(&subtitle);
+                    }
+                }
+            });
+        });
+        ca_bundle_row.add_suffix(&ca_bundle_button);
+        
+        let ca_bundle_reset_button = Button::builder()
+            .icon_name("edit-clear-symbolic")
+            .valign(gtk::Align::Center)
+            .tooltip_text("Only trust system certificates")
+            .build();
+        ca_bundle_reset_button.add_css_class("flat");
+        
+        let (db_ca_reset, downloader_ca_reset, toast_overlay_ca_reset) = network_context.clone();
+        let ca_bundle_row_reset = ca_bundle_row.clone();
+        ca_bundle_reset_button.connect_clicked(move |_| {
+            if Self::update_network_settings(&db_ca_reset, &downloader_ca_reset, &toast_overlay_ca_reset, |settings| settings.ca_bundle = None) {
+                ca_bundle_row_reset.set_subtitle("None, only system certificates are trusted");
+            }
+        });
+        ca_bundle_row.add_suffix(&ca_bundle_reset_button);
+        proxy_group.add(&ca_bundle_row);
+        
+        let insecure_row = adw::ActionRow::builder()
+            .title("Skip Certificate Checks")
+            .subtitle("Insecure, only for testing against local servers")
+            .build();
+        let insecure_switch = gtk::Switch::builder()
+            .valign(gtk::Align::Center)
+            .active(network_settings.accept_invalid_certs)
+            .build();
+        insecure_row.add_suffix(&insecure_switch);
+        insecure_row.set_activatable_widget(Some(&insecure_switch));
+        let (db_insecure, downloader_insecure, toast_overlay_insecure) = network_context;
+        insecure_switch.connect_active_notify(move |switch| {
+            let enabled = switch.is_active();
+            Self::update_network_settings(&db_insecure, &downloader_insecure, &toast_overlay_insecure, |settings| {
+                settings.accept_invalid_certs = enabled;
+            });
+        });
+        proxy_group.add(&insecure_row);
+        
+        page.add(&proxy_group);
+        
+        // Mirrors group
+        let mirrors_group = adw::PreferencesGroup::builder()
+            .title("Mirrors")
+            .description("Download from these URLs before the release itself. {tool}, {version} and {file} are filled in; the release checksum is checked for every mirror.")
+            .build();
+        
+        for tool_name in ["GE-Proton", "Wine-GE", "Spritz-Wine", "dwproton"] {
+            let mirrors_row = adw::EntryRow::builder()
+                .title(tool_name)
+                .text(tool_manager.get_mirrors(tool_name).join(" "))
+                .show_apply_button(true)
+                .build();
+            mirrors_row.set_tooltip_text(Some("e.g. https://mirror.example.com/{tool}/{version}/{file}; separate several with spaces"));
+            
+            let tool_manager_mirrors = tool_manager.clone();
+            let toast_overlay_mirrors = toast_overlay.clone();
+            mirrors_row.connect_apply(move |row| {
+                let templates: Vec<String> = row.text().split_whitespace().map(str::to_string).collect();
+                if let Err(e) = tool_manager_mirrors.set_mirrors(tool_name, &templates) {
+                    let toast = adw::Toast::new(&format!("Failed to save mirrors: {}", e));
+                    toast.set_timeout(5);
+                    toast_overlay_mirrors.add_toast(toast);
+                }
+            });
+            mirrors_group.add(&mirrors_row);
+        }
+        
+        let probe_row = adw::ActionRow::builder()
+            .title("Use Fastest Mirror")
+            .subtitle("Briefly test every mirror and the release before each download")
+            .build();
+        let probe_switch = gtk::Switch::builder()
+            .valign(gtk::Align::Center)
+            .active(db.get_probe_mirrors().unwrap_or(false))
+            .build();
+        probe_row.add_suffix(&probe_switch);
+        probe_row.set_activatable_widget(Some(&probe_switch));
+        let db_probe = db.clone();
+        let downloader_probe = downloader.clone();
+        let toast_overlay_probe = toast_overlay.clone();
+        probe_switch.connect_active_notify(move |switch| {
+            let enabled = switch.is_active();
+            match db_probe.set_probe_mirrors(enabled) {
+                Ok(()) => downloader_probe.set_probe_mirrors(enabled),
+                Err(e) => {
+                    let toast = adw::Toast::new(&format!("Failed to save download settings: {}", e));
+                    toast.set_timeout(5);
+                    toast_overlay_probe.add_toast(toast);
+                }
+            }
+        });
+        mirrors_group.add(&probe_row);
+        
+        page.add(&mirrors_group);
+        
         // Auto-update group
         let update_group = adw::PreferencesGroup::builder()
             .title("Updates")
@@ -753,6 +1808,27 @@ impl MainWindow {
         dialog.present();
     }
 
+    /// Applies a change to the saved network settings, which the release API
+    /// and downloads share. Invalid settings are reported and not saved.
+    fn update_network_settings(
+        db: &Database,
+        downloader: &Downloader,
+        toast_overlay: &adw::ToastOverlay,
+        update: impl FnOnce(&mut crate::backend::network::NetworkSettings),
+    ) -> bool {
+        let mut settings = db.get_network_settings().unwrap_or_default();
+        update(&mut settings);
+        let result = downloader.configure_network(settings.clone())
+            .and_then(|()| db.set_network_settings(&settings));
+        if let Err(e) = result {
+            let toast = adw::Toast::new(&format!("Failed to apply network settings: {}", e.user_message()));
+            toast.set_timeout(5);
+            toast_overlay.add_toast(toast);
+            return false;
+        }
+        true
+    }
+
     fn show_about_dialog(window: &adw::ApplicationWindow) {
         let about = adw::AboutWindow::builder()
             .transient_for(window)